@@ -1,3 +1,29 @@
+// 声明应用命令后，页面只能调用 capabilities 中授权的命令
+// 控制命令只供 RPC 和 CLI 调用，不在此声明，页面调用时会被拒绝
+const COMMANDS: &[&str] = &[
+  "save_credentials",
+  "get_credentials",
+  "delete_credentials",
+  "auto_login",
+  "inject_browser_emulation",
+  "archive_conversation",
+  "search_history",
+  "open_history_item",
+  "get_settings",
+  "update_settings",
+  "get_translations",
+  "export_config",
+  "import_config",
+  "get_recent_logs",
+  "collect_diagnostics",
+  "suspend_auto_hide",
+  "generation_started",
+  "generation_finished",
+];
+
 fn main() {
-  tauri_build::build()
+  tauri_build::try_build(
+    tauri_build::Attributes::new().app_manifest(tauri_build::AppManifest::new().commands(COMMANDS)),
+  )
+  .expect("failed to run tauri-build");
 }
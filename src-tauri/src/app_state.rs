@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::sync::{PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+// 定义应用状态结构体
+#[derive(Debug, Clone, Default)]
+pub struct AppState {
+    // 处于前台的窗口
+    pub focused_windows: Vec<String>,
+    // 上次点击托盘图标的时间
+    pub last_tray_click_time: Option<Instant>,
+    // 最近一次点击托盘图标与前一次点击的间隔
+    pub last_tray_click_interval: Option<Duration>,
+    // 最近一次因失去焦点而自动隐藏的窗口和时间
+    pub last_auto_hide: Option<(String, Instant)>,
+    // 打开了文件选择框或登录弹出窗口、暂不自动隐藏的窗口
+    pub auto_hide_suspended: Vec<String>,
+    // 服务窗口的最近使用顺序（最近使用的在前）
+    pub service_mru: Vec<String>,
+    // 正在生成回答的服务
+    pub generating_services: Vec<String>,
+    // 有未查看回答的服务（最新的在前）
+    pub pending_responses: Vec<String>,
+    // 各服务最近一次生成的回答
+    pub last_responses: HashMap<String, String>,
+}
+
+// 定义修改应用状态的事件
+#[derive(Debug, Clone)]
+pub enum StateEvent {
+    // 窗口获得或失去焦点
+    WindowFocused { label: String, focused: bool },
+    // 点击了托盘图标
+    TrayClicked { at: Instant },
+    // 窗口因失去焦点而自动隐藏
+    AutoHidden { label: String, at: Instant },
+    // 页面打开了文件选择框或登录弹出窗口，窗口重新获得焦点前不自动隐藏
+    AutoHideSuspended { label: String },
+    // 服务窗口被使用
+    ServiceActivated { service: String },
+    // 服务开始生成回答
+    GenerationStarted { service: String },
+    // 服务完成生成回答，`hidden` 表示完成时窗口不可见
+    GenerationFinished { service: String, response: String, hidden: bool },
+}
+
+impl AppState {
+    // 窗口是否处于前台
+    pub fn is_focused(&self, label: &str) -> bool {
+        self.focused_windows.iter().any(|l| l == label)
+    }
+
+    // 窗口是否在指定时间之前的一小段时间内被自动隐藏
+    pub fn recently_auto_hidden(&self, label: &str, now: Instant, within: Duration) -> bool {
+        self.last_auto_hide
+            .as_ref()
+            .is_some_and(|(l, at)| l == label && now.saturating_duration_since(*at) < within)
+    }
+
+    // 应用一个事件
+    fn apply(&mut self, event: StateEvent) {
+        match event {
+            StateEvent::WindowFocused { label, focused } => {
+                self.focused_windows.retain(|l| *l != label);
+                if focused {
+                    self.auto_hide_suspended.retain(|l| *l != label);
+                    self.focused_windows.push(label);
+                }
+            }
+            StateEvent::TrayClicked { at } => {
+                self.last_tray_click_interval = self.last_tray_click_time.map(|last| at.saturating_duration_since(last));
+                self.last_tray_click_time = Some(at);
+            }
+            StateEvent::AutoHidden { label, at } => {
+                self.last_auto_hide = Some((label, at));
+            }
+            StateEvent::AutoHideSuspended { label } => {
+                if !self.auto_hide_suspended.contains(&label) {
+                    self.auto_hide_suspended.push(label);
+                }
+            }
+            StateEvent::ServiceActivated { service } => {
+                // 窗口获得焦点即视为已查看回答
+                self.pending_responses.retain(|s| *s != service);
+                self.service_mru.retain(|s| *s != service);
+                self.service_mru.insert(0, service);
+            }
+            StateEvent::GenerationStarted { service } => {
+                if !self.generating_services.contains(&service) {
+                    self.generating_services.push(service);
+                }
+            }
+            StateEvent::GenerationFinished { service, response, hidden } => {
+                self.generating_services.retain(|s| *s != service);
+                if hidden {
+                    self.pending_responses.retain(|s| *s != service);
+                    self.pending_responses.insert(0, service.clone());
+                }
+                self.last_responses.insert(service, response);
+            }
+        }
+    }
+}
+
+// 应用状态的唯一所有者：所有修改都以事件的形式按到达顺序应用，读取时获得一致的快照。
+// 锁被占用时等待而不是放弃，避免丢失焦点变化或托盘点击
+#[derive(Default)]
+pub struct StateStore(RwLock<AppState>);
+
+impl StateStore {
+    // 应用事件，返回应用后的状态
+    pub fn dispatch(&self, event: StateEvent) -> AppState {
+        // 事件处理不会在中途失败，锁中毒时状态仍然是完整的
+        let mut state = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        state.apply(event);
+        state.clone()
+    }
+
+    // 获取当前状态的快照
+    pub fn snapshot(&self) -> AppState {
+        self.0.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn apply_all(events: impl IntoIterator<Item = StateEvent>) -> AppState {
+        let mut state = AppState::default();
+        for event in events {
+            state.apply(event);
+        }
+        state
+    }
+
+    fn focused(label: &str, focused: bool) -> StateEvent {
+        StateEvent::WindowFocused { label: label.to_string(), focused }
+    }
+
+    fn activated(service: &str) -> StateEvent {
+        StateEvent::ServiceActivated { service: service.to_string() }
+    }
+
+    fn started(service: &str) -> StateEvent {
+        StateEvent::GenerationStarted { service: service.to_string() }
+    }
+
+    fn finished(service: &str, response: &str, hidden: bool) -> StateEvent {
+        StateEvent::GenerationFinished {
+            service: service.to_string(),
+            response: response.to_string(),
+            hidden,
+        }
+    }
+
+    #[test]
+    fn activation_orders_services_by_recent_use() {
+        let state = apply_all([activated("gemini"), activated("poe"), activated("gemini")]);
+
+        assert_eq!(state.service_mru, ["gemini", "poe"]);
+    }
+
+    #[test]
+    fn responses_finished_while_hidden_are_badged_until_activated() {
+        let now = Instant::now();
+        let state = apply_all([
+            activated("gemini"),
+            started("gemini"),
+            focused("gemini", true),
+            focused("gemini", false),
+            StateEvent::AutoHidden { label: "gemini".to_string(), at: now },
+            started("poe"),
+            finished("gemini", "first", true),
+            finished("poe", "second", true),
+            activated("poe"),
+        ]);
+
+        // 激活 Poe 后只清除 Poe 的标记，Gemini 仍未查看
+        assert_eq!(state.pending_responses, ["gemini"]);
+        assert!(state.generating_services.is_empty());
+        assert_eq!(state.last_responses["gemini"], "first");
+        assert_eq!(state.last_responses["poe"], "second");
+        assert_eq!(state.service_mru, ["poe", "gemini"]);
+        assert!(state.focused_windows.is_empty());
+        assert!(state.recently_auto_hidden("gemini", now, Duration::from_millis(300)));
+    }
+
+    #[test]
+    fn responses_finished_while_visible_are_not_badged() {
+        let state = apply_all([started("gemini"), finished("gemini", "visible", false), started("gemini")]);
+
+        assert!(state.pending_responses.is_empty());
+        assert_eq!(state.generating_services, ["gemini"]);
+        assert_eq!(state.last_responses["gemini"], "visible");
+    }
+
+    #[test]
+    fn newest_pending_response_comes_first() {
+        let state = apply_all([
+            finished("gemini", "a", true),
+            finished("poe", "b", true),
+            finished("gemini", "c", true),
+        ]);
+
+        assert_eq!(state.pending_responses, ["gemini", "poe"]);
+        assert_eq!(state.last_responses["gemini"], "c");
+    }
+
+    #[test]
+    fn focus_clears_the_auto_hide_suspension() {
+        let state = apply_all([
+            focused("poe", true),
+            StateEvent::AutoHideSuspended { label: "gemini".to_string() },
+            StateEvent::AutoHideSuspended { label: "gemini".to_string() },
+            focused("poe", false),
+        ]);
+        assert_eq!(state.auto_hide_suspended, ["gemini"]);
+        assert!(!state.is_focused("poe"));
+
+        let state = apply_all([StateEvent::AutoHideSuspended { label: "gemini".to_string() }, focused("gemini", true)]);
+        assert!(state.auto_hide_suspended.is_empty());
+        assert!(state.is_focused("gemini"));
+    }
+
+    #[test]
+    fn auto_hide_is_only_recent_for_the_same_window() {
+        let at = Instant::now();
+        let state = apply_all([StateEvent::AutoHidden { label: "gemini".to_string(), at }]);
+        let guard = Duration::from_millis(300);
+
+        assert!(state.recently_auto_hidden("gemini", at + Duration::from_millis(100), guard));
+        assert!(!state.recently_auto_hidden("gemini", at + guard, guard));
+        assert!(!state.recently_auto_hidden("poe", at, guard));
+    }
+
+    #[test]
+    fn tray_clicks_record_the_interval() {
+        let at = Instant::now();
+        let state = apply_all([
+            StateEvent::TrayClicked { at },
+            focused("gemini", true),
+            StateEvent::TrayClicked { at: at + Duration::from_millis(150) },
+        ]);
+
+        assert_eq!(state.last_tray_click_interval, Some(Duration::from_millis(150)));
+        assert_eq!(state.last_tray_click_time, Some(at + Duration::from_millis(150)));
+    }
+
+    #[test]
+    fn concurrent_dispatches_are_all_applied() {
+        let store = Arc::new(StateStore::default());
+
+        let threads: Vec<_> = ["gemini", "poe"]
+            .into_iter()
+            .map(|service| {
+                let store = Arc::clone(&store);
+                std::thread::spawn(move || {
+                    for i in 0..100 {
+                        store.dispatch(started(service));
+                        store.dispatch(finished(service, &i.to_string(), true));
+                        store.dispatch(activated(service));
+                    }
+                    store.dispatch(finished(service, "last", true));
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let state = store.snapshot();
+        assert!(state.generating_services.is_empty());
+        assert_eq!(state.pending_responses.len(), 2);
+        assert_eq!(state.service_mru.len(), 2);
+        assert_eq!(state.last_responses["gemini"], "last");
+        assert_eq!(state.last_responses["poe"], "last");
+    }
+}
@@ -0,0 +1,287 @@
+use crate::{i18n, services};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::Url;
+
+// 本地对话归档，使用 SQLite FTS5 建立全文索引
+pub struct Archive {
+    conn: Mutex<Connection>,
+}
+
+// 定义历史记录条目结构体
+#[derive(Debug, Serialize, Clone)]
+pub struct HistoryItem {
+    pub id: i64,
+    pub service: String,
+    pub url: String,
+    pub title: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub snippet: String,
+}
+
+// 定义完整对话结构体，用于导出
+#[derive(Debug, Serialize, Clone)]
+pub struct Conversation {
+    pub service: String,
+    pub url: String,
+    pub title: String,
+    pub content: String,
+    pub updated_at: i64,
+}
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS conversations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    service TEXT NOT NULL,
+    url TEXT NOT NULL,
+    title TEXT NOT NULL DEFAULT '',
+    content TEXT NOT NULL DEFAULT '',
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    UNIQUE(service, url)
+);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS conversations_fts USING fts5(
+    title, content, content='conversations', content_rowid='id'
+);
+
+CREATE TRIGGER IF NOT EXISTS conversations_ai AFTER INSERT ON conversations BEGIN
+    INSERT INTO conversations_fts(rowid, title, content) VALUES (new.id, new.title, new.content);
+END;
+
+CREATE TRIGGER IF NOT EXISTS conversations_ad AFTER DELETE ON conversations BEGIN
+    INSERT INTO conversations_fts(conversations_fts, rowid, title, content) VALUES ('delete', old.id, old.title, old.content);
+END;
+
+CREATE TRIGGER IF NOT EXISTS conversations_au AFTER UPDATE ON conversations BEGIN
+    INSERT INTO conversations_fts(conversations_fts, rowid, title, content) VALUES ('delete', old.id, old.title, old.content);
+    INSERT INTO conversations_fts(rowid, title, content) VALUES (new.id, new.title, new.content);
+END;
+"#;
+
+impl Archive {
+    // 打开（或创建）归档数据库
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let conn = Connection::open(path).map_err(|e| e.to_string())?;
+        conn.execute_batch(SCHEMA).map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    // 保存对话，同一服务的同一 URL 只保留最新内容
+    pub fn record(&self, service: &str, url: &str, title: &str, content: &str) -> Result<(), String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+
+        conn.execute(
+            "INSERT INTO conversations (service, url, title, content, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+             ON CONFLICT(service, url) DO UPDATE SET
+                 title = excluded.title,
+                 content = excluded.content,
+                 updated_at = excluded.updated_at
+             WHERE conversations.title <> excluded.title OR conversations.content <> excluded.content",
+            params![service, url, title, content, now()],
+        )
+        .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    // 全文搜索；查询为空时按更新时间返回最近的对话
+    pub fn search(&self, query: &str, limit: i64) -> Result<Vec<HistoryItem>, String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let query = fts_query(query);
+
+        let items = if query.is_empty() {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, service, url, title, created_at, updated_at, substr(content, 1, 120)
+                     FROM conversations
+                     ORDER BY updated_at DESC
+                     LIMIT ?1",
+                )
+                .map_err(|e| e.to_string())?;
+            let rows = stmt.query_map(params![limit], row_to_item).map_err(|e| e.to_string())?;
+            rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?
+        } else {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT c.id, c.service, c.url, c.title, c.created_at, c.updated_at,
+                            snippet(conversations_fts, 1, '[', ']', '…', 16)
+                     FROM conversations_fts
+                     JOIN conversations c ON c.id = conversations_fts.rowid
+                     WHERE conversations_fts MATCH ?1
+                     ORDER BY rank
+                     LIMIT ?2",
+                )
+                .map_err(|e| e.to_string())?;
+            let rows = stmt.query_map(params![query, limit], row_to_item).map_err(|e| e.to_string())?;
+            rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?
+        };
+
+        Ok(items)
+    }
+
+    // 根据 ID 获取历史记录
+    pub fn get(&self, id: i64) -> Result<Option<HistoryItem>, String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+
+        conn.query_row(
+            "SELECT id, service, url, title, created_at, updated_at, substr(content, 1, 120)
+             FROM conversations
+             WHERE id = ?1",
+            params![id],
+            row_to_item,
+        )
+        .optional()
+        .map_err(|e| e.to_string())
+    }
+
+    // 获取要打开的历史对话，只返回属于服务站点的页面
+    pub fn resolve(&self, id: i64) -> Result<(HistoryItem, Url), String> {
+        let item = self
+            .get(id)?
+            .ok_or_else(|| i18n::tr("error-history-not-found", &[("id", &id.to_string())]))?;
+        let url = service_url(&item.service, &item.url)?;
+
+        Ok((item, url))
+    }
+
+    // 获取服务某个 URL 下已归档的完整对话
+    pub fn conversation(&self, service: &str, url: &str) -> Result<Option<Conversation>, String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+
+        conn.query_row(
+            "SELECT service, url, title, content, updated_at
+             FROM conversations
+             WHERE service = ?1 AND url = ?2",
+            params![service, url],
+            |row| {
+                Ok(Conversation {
+                    service: row.get(0)?,
+                    url: row.get(1)?,
+                    title: row.get(2)?,
+                    content: row.get(3)?,
+                    updated_at: row.get(4)?,
+                })
+            },
+        )
+        .optional()
+        .map_err(|e| e.to_string())
+    }
+}
+
+// 检查 URL 属于服务自身的站点，避免页面归档或打开其他网站
+pub fn service_url(service: &str, url: &str) -> Result<Url, String> {
+    let def = services::find(service).ok_or_else(|| i18n::tr("error-unsupported-service", &[("service", service)]))?;
+    let url = Url::parse(url).map_err(|e| e.to_string())?;
+    if !def.owns_url(&url) {
+        return Err(i18n::tr("error-foreign-url", &[("service", service), ("url", url.as_str())]));
+    }
+
+    Ok(url)
+}
+
+fn row_to_item(row: &rusqlite::Row<'_>) -> rusqlite::Result<HistoryItem> {
+    Ok(HistoryItem {
+        id: row.get(0)?,
+        service: row.get(1)?,
+        url: row.get(2)?,
+        title: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+        snippet: row.get(6)?,
+    })
+}
+
+// 将用户输入转换为 FTS5 查询：每个词按前缀匹配，并转义其中的引号
+fn fts_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_dir;
+
+    fn archive() -> Archive {
+        Archive::open(&temp_dir("archive").join("history.sqlite3")).unwrap()
+    }
+
+    #[test]
+    fn records_and_searches_conversations() {
+        let archive = archive();
+        archive.record("poe", "https://poe.com/chat/1", "Rust lifetimes", "How do borrows work?").unwrap();
+        archive.record("gemini", "https://gemini.google.com/app/2", "Travel", "Plan a trip to Kyoto").unwrap();
+        // 同一页面再次归档时更新内容，不新增记录
+        archive.record("poe", "https://poe.com/chat/1", "Rust lifetimes", "How do borrows and lifetimes work?").unwrap();
+
+        let items = archive.search("lifetim", 10).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].url, "https://poe.com/chat/1");
+        assert!(items[0].snippet.contains("[lifetimes]"));
+
+        assert_eq!(archive.search("", 10).unwrap().len(), 2);
+        assert!(archive.search("\"unbalanced", 10).unwrap().is_empty());
+        assert_eq!(
+            archive.conversation("poe", "https://poe.com/chat/1").unwrap().unwrap().content,
+            "How do borrows and lifetimes work?"
+        );
+    }
+
+    #[test]
+    fn resolves_archived_conversations_to_open() {
+        let archive = archive();
+        archive.record("gemini", "https://gemini.google.com/app/2", "Travel", "Plan a trip").unwrap();
+        let id = archive.search("trip", 10).unwrap()[0].id;
+
+        let (item, url) = archive.resolve(id).unwrap();
+        assert_eq!(item.service, "gemini");
+        assert_eq!(url.as_str(), "https://gemini.google.com/app/2");
+
+        assert_eq!(archive.resolve(id + 100).unwrap_err(), i18n::tr("error-history-not-found", &[("id", &(id + 100).to_string())]));
+    }
+
+    #[test]
+    fn rejects_urls_outside_the_service() {
+        assert!(service_url("poe", "https://poe.com/chat/1").is_ok());
+        for (service, url) in [
+            ("poe", "https://evil.example/poe.com"),
+            ("poe", "http://poe.com/chat/1"),
+            ("poe", "https://gemini.google.com/app"),
+            ("gemini", "javascript:alert(1)"),
+        ] {
+            assert!(service_url(service, url).is_err(), "{service} {url}");
+        }
+        assert_eq!(
+            service_url("chatgpt", "https://chatgpt.com").unwrap_err(),
+            i18n::tr("error-unsupported-service", &[("service", "chatgpt")])
+        );
+
+        // 数据库中已有的其他站点记录也不会被打开
+        let archive = archive();
+        archive.record("poe", "https://evil.example/", "Poe", "content").unwrap();
+        let id = archive.search("", 10).unwrap()[0].id;
+        assert_eq!(
+            archive.resolve(id).unwrap_err(),
+            i18n::tr("error-foreign-url", &[("service", "poe"), ("url", "https://evil.example/")])
+        );
+    }
+}
@@ -0,0 +1,56 @@
+use crate::app_state::{StateEvent, StateStore};
+use crate::{logging, services, tray, window_modes, window_state};
+use std::time::{Duration, Instant};
+use tauri::{Manager, Runtime, WebviewWindow};
+
+// 失去焦点后等待这段时间再隐藏，焦点短暂切换（如拖动窗口、系统弹出提示）时不隐藏
+const GRACE_PERIOD: Duration = Duration::from_millis(400);
+
+// 窗口失去焦点时是否应自动隐藏：弹出模式总是自动隐藏
+pub fn enabled<R: Runtime>(window: &WebviewWindow<R>) -> bool {
+    let modes = window_modes::get(window.app_handle(), window.label());
+    modes.auto_hide || modes.popover
+}
+
+// 注入焦点保护脚本：页面打开文件选择框或登录弹出窗口时暂停自动隐藏
+pub fn inject_guard<R: Runtime>(window: &WebviewWindow<R>) -> Result<(), String> {
+    window.eval(include_str!("auto_hide_guard.js")).map_err(|e| e.to_string())
+}
+
+// 窗口失去焦点时调用，等待一段时间后仍未回到应用时隐藏窗口
+pub fn on_focus_lost<R: Runtime>(window: WebviewWindow<R>) {
+    if !enabled(&window) {
+        return;
+    }
+
+    std::thread::spawn(move || {
+        std::thread::sleep(GRACE_PERIOD);
+
+        let app_handle = window.app_handle();
+        let state = app_handle.state::<StateStore>().snapshot();
+        if !window.is_visible().unwrap_or(false)
+            || window.is_focused().unwrap_or(false)
+            || state.auto_hide_suspended.iter().any(|l| l == window.label())
+            || popup_focused(&window)
+        {
+            return;
+        }
+
+        logging::log_err!(window_state::hide(&window), "隐藏窗口失败");
+        app_handle.state::<StateStore>().dispatch(StateEvent::AutoHidden {
+            label: window.label().to_string(),
+            at: Instant::now(),
+        });
+        logging::log_err!(tray::refresh(app_handle), "更新托盘失败");
+    });
+}
+
+// 焦点是否在页面打开的弹出窗口上（如 OAuth 登录窗口），服务窗口和设置窗口不算
+fn popup_focused<R: Runtime>(window: &WebviewWindow<R>) -> bool {
+    window
+        .app_handle()
+        .webview_windows()
+        .values()
+        .filter(|w| w.label() != "settings" && services::find(w.label()).is_none())
+        .any(|w| w.is_focused().unwrap_or(false))
+}
@@ -0,0 +1,36 @@
+// 焦点保护脚本
+// 页面打开文件选择框或登录弹出窗口时通知 Rust，窗口重新获得焦点前不会因失去焦点而自动隐藏
+
+(function() {
+    // 避免重复注入
+    if (window.__aiAutoHideGuard) {
+        return;
+    }
+    window.__aiAutoHideGuard = true;
+
+    function suspendAutoHide() {
+        const ipc = window.__TAURI_INTERNALS__;
+        if (!ipc) {
+            return;
+        }
+
+        ipc.invoke('suspend_auto_hide', {}).catch(error => {
+            console.error('暂停自动隐藏时出错:', error);
+        });
+    }
+
+    // 点击文件输入框会打开系统的文件选择框
+    document.addEventListener('click', event => {
+        const target = event.target;
+        if (target instanceof HTMLInputElement && target.type === 'file') {
+            suspendAutoHide();
+        }
+    }, true);
+
+    // OAuth 登录通常通过 window.open 打开弹出窗口
+    const originalOpen = window.open;
+    window.open = function(...args) {
+        suspendAutoHide();
+        return originalOpen.apply(this, args);
+    };
+})();
@@ -0,0 +1,311 @@
+use std::fs;
+use std::path::PathBuf;
+
+// 开机启动时传入的参数，启动后只在托盘中运行
+pub const HIDDEN_ARG: &str = "--hidden";
+
+// 定义开机启动的命令
+#[derive(Debug, Clone)]
+pub struct LaunchCommand {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+}
+
+// 定义开机启动的实现，各平台使用不同的机制；应用通过托管状态使用，测试中可以替换
+pub trait Autostart: Send + Sync {
+    // 是否已经设置开机启动
+    fn is_enabled(&self) -> bool;
+    // 设置开机启动
+    fn enable(&self, command: &LaunchCommand) -> Result<(), String>;
+    // 取消开机启动
+    fn disable(&self) -> Result<(), String>;
+}
+
+// 获取当前平台的实现
+pub fn system() -> Box<dyn Autostart> {
+    #[cfg(target_os = "macos")]
+    let autostart = LaunchAgent::new(home_dir().join("Library/LaunchAgents"));
+    #[cfg(windows)]
+    let autostart = RegistryRun;
+    #[cfg(not(any(target_os = "macos", windows)))]
+    let autostart = XdgAutostart::new(xdg_config_dir().join("autostart"));
+
+    Box::new(autostart)
+}
+
+// 设置或取消开机启动，启动时不显示窗口
+pub fn set_enabled(autostart: &dyn Autostart, enabled: bool) -> Result<(), String> {
+    if !enabled {
+        return autostart.disable();
+    }
+
+    // AppImage 运行时可执行文件位于临时挂载目录中，需要使用 AppImage 文件本身
+    let program = match std::env::var_os("APPIMAGE") {
+        Some(appimage) => PathBuf::from(appimage),
+        None => std::env::current_exe().map_err(|e| e.to_string())?,
+    };
+
+    autostart.enable(&LaunchCommand {
+        program,
+        args: vec![HIDDEN_ARG.to_string()],
+    })
+}
+
+// Linux 等桌面环境：在 XDG 自动启动目录中写入 .desktop 文件
+#[cfg(not(any(target_os = "macos", windows)))]
+pub struct XdgAutostart {
+    dir: PathBuf,
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+impl XdgAutostart {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn entry_path(&self) -> PathBuf {
+        self.dir.join("ai-assistant.desktop")
+    }
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+impl Autostart for XdgAutostart {
+    fn is_enabled(&self) -> bool {
+        self.entry_path().exists()
+    }
+
+    fn enable(&self, command: &LaunchCommand) -> Result<(), String> {
+        let exec: Vec<String> = std::iter::once(command.program.to_string_lossy().into_owned())
+            .chain(command.args.iter().cloned())
+            .map(|arg| desktop_quote(&arg))
+            .collect();
+        let entry = format!(
+            "[Desktop Entry]\nType=Application\nName=AI Assistant\nExec={}\nTerminal=false\nX-GNOME-Autostart-enabled=true\n",
+            exec.join(" ")
+        );
+
+        fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        fs::write(self.entry_path(), entry).map_err(|e| e.to_string())
+    }
+
+    fn disable(&self) -> Result<(), String> {
+        match fs::remove_file(self.entry_path()) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+// 按照 Desktop Entry 规范给 Exec 中的参数加引号
+// 读取时先按字符串规则还原 `\\`，再解析引号，所以写入时要在加引号之后再把反斜杠加倍
+#[cfg(not(any(target_os = "macos", windows)))]
+fn desktop_quote(arg: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+
+    quoted.replace('\\', "\\\\")
+}
+
+// macOS：在 LaunchAgents 目录中写入登录时运行的 plist 文件
+#[cfg(target_os = "macos")]
+pub struct LaunchAgent {
+    dir: PathBuf,
+}
+
+#[cfg(target_os = "macos")]
+impl LaunchAgent {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn plist_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.plist", crate::ipc::SOCKET_NAME))
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl Autostart for LaunchAgent {
+    fn is_enabled(&self) -> bool {
+        self.plist_path().exists()
+    }
+
+    fn enable(&self, command: &LaunchCommand) -> Result<(), String> {
+        let arguments: String = std::iter::once(command.program.to_string_lossy().into_owned())
+            .chain(command.args.iter().cloned())
+            .map(|arg| format!("        <string>{}</string>\n", xml_escape(&arg)))
+            .collect();
+        let plist = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+{}    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+            crate::ipc::SOCKET_NAME,
+            arguments
+        );
+
+        fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        fs::write(self.plist_path(), plist).map_err(|e| e.to_string())
+    }
+
+    fn disable(&self) -> Result<(), String> {
+        match fs::remove_file(self.plist_path()) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+// Windows：在当前用户注册表的 Run 键中写入启动命令
+#[cfg(windows)]
+pub struct RegistryRun;
+
+#[cfg(windows)]
+impl RegistryRun {
+    const KEY: &'static str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+    const VALUE: &'static str = "AI Assistant";
+
+    fn reg(args: &[&str]) -> Result<bool, String> {
+        use std::os::windows::process::CommandExt;
+
+        // 不显示控制台窗口
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        let status = std::process::Command::new("reg")
+            .args(args)
+            .creation_flags(CREATE_NO_WINDOW)
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .map_err(|e| e.to_string())?;
+
+        Ok(status.success())
+    }
+}
+
+#[cfg(windows)]
+impl Autostart for RegistryRun {
+    fn is_enabled(&self) -> bool {
+        Self::reg(&["query", Self::KEY, "/v", Self::VALUE]).unwrap_or(false)
+    }
+
+    fn enable(&self, command: &LaunchCommand) -> Result<(), String> {
+        let data: Vec<String> = std::iter::once(format!("\"{}\"", command.program.display()))
+            .chain(command.args.iter().cloned())
+            .collect();
+        let data = data.join(" ");
+
+        if Self::reg(&["add", Self::KEY, "/v", Self::VALUE, "/t", "REG_SZ", "/d", &data, "/f"])? {
+            Ok(())
+        } else {
+            Err(crate::i18n::tr("error-registry-write", &[]))
+        }
+    }
+
+    fn disable(&self) -> Result<(), String> {
+        if self.is_enabled() && !Self::reg(&["delete", Self::KEY, "/v", Self::VALUE, "/f"])? {
+            return Err(crate::i18n::tr("error-registry-delete", &[]));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn home_dir() -> PathBuf {
+    std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default()
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn xdg_config_dir() -> PathBuf {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .unwrap_or_else(|| std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default().join(".config"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings;
+    use crate::test_support::{mock_app, temp_dir};
+    use tauri::Manager;
+
+    fn command() -> LaunchCommand {
+        LaunchCommand {
+            program: PathBuf::from("/opt/AI Assistant/ai-assistant"),
+            args: vec![HIDDEN_ARG.to_string()],
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    #[test]
+    fn xdg_autostart_writes_a_desktop_entry() {
+        let dir = temp_dir("autostart").join("autostart");
+        let autostart = XdgAutostart::new(dir.clone());
+        assert!(!autostart.is_enabled());
+
+        autostart.enable(&command()).unwrap();
+
+        assert!(autostart.is_enabled());
+        let entry = fs::read_to_string(dir.join("ai-assistant.desktop")).unwrap();
+        assert!(entry.starts_with("[Desktop Entry]\n"));
+        assert!(entry.contains("\nExec=\"/opt/AI Assistant/ai-assistant\" \"--hidden\"\n"));
+    }
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    #[test]
+    fn xdg_autostart_disable_removes_the_entry() {
+        let autostart = XdgAutostart::new(temp_dir("autostart"));
+        autostart.enable(&command()).unwrap();
+
+        autostart.disable().unwrap();
+        assert!(!autostart.is_enabled());
+
+        // 未设置开机启动时取消也不出错
+        autostart.disable().unwrap();
+        assert!(!autostart.is_enabled());
+    }
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    #[test]
+    fn desktop_quote_escapes_special_characters() {
+        assert_eq!(desktop_quote("plain"), r#""plain""#);
+        assert_eq!(desktop_quote(r#"a "b" `c`"#), r#""a \\"b\\" \\`c\\`""#);
+        // 规范中的例子：字面的反斜杠写成四个反斜杠，字面的美元符号写成 `\\$`
+        assert_eq!(desktop_quote(r"C:\dir"), r#""C:\\\\dir""#);
+        assert_eq!(desktop_quote("$HOME"), r#""\\$HOME""#);
+    }
+
+    #[test]
+    fn settings_toggle_uses_the_managed_autostart() {
+        let app = mock_app();
+        let autostart = app.state::<Box<dyn Autostart>>();
+        assert!(!autostart.is_enabled());
+
+        settings::update(app.handle(), |settings| settings.launch_at_login = true).unwrap();
+        assert!(autostart.is_enabled());
+
+        settings::update(app.handle(), |settings| settings.launch_at_login = false).unwrap();
+        assert!(!autostart.is_enabled());
+    }
+}
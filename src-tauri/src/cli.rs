@@ -0,0 +1,260 @@
+use crate::{autostart, i18n, ipc};
+use serde::{Deserialize, Serialize};
+
+// 定义启动参数结构体
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LaunchArgs {
+    // 要显示的服务
+    pub service: Option<String>,
+    // 要打开的 URL
+    pub url: Option<String>,
+    // 要填入输入框的提示词
+    pub prompt: Option<String>,
+    // 启动时不显示窗口（开机启动时使用）
+    #[serde(default)]
+    pub hidden: bool,
+}
+
+impl LaunchArgs {
+    // 解析命令行参数，支持 `--service poe` 和 `--service=poe` 两种写法，忽略无法识别的参数
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Self {
+        let mut launch_args = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            if arg == autostart::HIDDEN_ARG {
+                launch_args.hidden = true;
+                continue;
+            }
+
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+
+            let slot = match flag.as_str() {
+                "--service" => &mut launch_args.service,
+                "--url" => &mut launch_args.url,
+                "--prompt" => &mut launch_args.prompt,
+                _ => continue,
+            };
+
+            *slot = inline_value.or_else(|| args.next());
+        }
+
+        launch_args
+    }
+}
+
+// 定义导出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    #[serde(rename = "md")]
+    Markdown,
+    Json,
+}
+
+// 定义命令行子命令，由正在运行的实例执行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    // 显示服务窗口
+    Show { service: String },
+    // 隐藏所有窗口
+    Hide,
+    // 向服务发送提示词
+    Ask { service: String, prompt: String },
+    // 打开 URL
+    Open { url: String },
+    // 导出服务当前页面的对话
+    Export { service: String, format: ExportFormat },
+    // 获取运行状态
+    Status,
+}
+
+// 设置了控制令牌时，命令行客户端从这个环境变量读取令牌
+pub const TOKEN_ENV: &str = "AI_ASSISTANT_TOKEN";
+
+// 命令行用法说明
+fn usage() -> String {
+    i18n::tr("cli-usage", &[])
+}
+
+impl Command {
+    // 解析子命令；第一个参数不是子命令时返回 `None`，继续按启动参数处理
+    pub fn parse(args: &[String]) -> Option<Result<Self, String>> {
+        let (name, rest) = args.split_first()?;
+
+        let command = match (name.as_str(), rest) {
+            ("show", [service]) => Ok(Self::Show {
+                service: service.clone(),
+            }),
+            ("hide", []) => Ok(Self::Hide),
+            ("ask", [service, prompt @ ..]) if !prompt.is_empty() => Ok(Self::Ask {
+                service: service.clone(),
+                prompt: prompt.join(" "),
+            }),
+            ("open", [url]) => Ok(Self::Open { url: url.clone() }),
+            ("export", [service, options @ ..]) => {
+                parse_export_format(options).map(|format| Self::Export {
+                    service: service.clone(),
+                    format,
+                })
+            }
+            ("status", []) => Ok(Self::Status),
+            ("show" | "hide" | "ask" | "open" | "export" | "status", _) => Err(usage()),
+            _ => return None,
+        };
+
+        Some(command)
+    }
+}
+
+// 解析 `--format md` 或 `--format=md`，默认导出为 Markdown
+fn parse_export_format(options: &[String]) -> Result<ExportFormat, String> {
+    let value = match options {
+        [] => return Ok(ExportFormat::Markdown),
+        [flag, value] if flag == "--format" => value.as_str(),
+        [flag] => flag.strip_prefix("--format=").ok_or_else(usage)?,
+        _ => return Err(usage()),
+    };
+
+    match value {
+        "md" | "markdown" => Ok(ExportFormat::Markdown),
+        "json" => Ok(ExportFormat::Json),
+        _ => Err(i18n::tr("cli-unsupported-format", &[("format", value)])),
+    }
+}
+
+// 将子命令发送给正在运行的实例，输出 JSON 结果并返回进程退出码
+pub fn run(command: Result<Command, String>) -> i32 {
+    let (response, code) = match command {
+        Err(e) => (ipc::Response::error(e), 2),
+        Ok(command) => match ipc::send(&ipc::Request::Command(ipc::CommandRequest {
+            token: std::env::var(TOKEN_ENV).ok().filter(|token| !token.is_empty()),
+            command,
+        })) {
+            Ok(Some(response)) => {
+                let code = if response.ok { 0 } else { 1 };
+                (response, code)
+            }
+            Ok(None) => (ipc::Response::error(i18n::tr("cli-not-running", &[])), 1),
+            Err(e) => (ipc::Response::error(e.to_string()), 1),
+        },
+    };
+
+    match serde_json::to_string_pretty(&response) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("{}", e),
+    }
+
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn parse(line: &[&str]) -> Option<Result<Command, String>> {
+        Command::parse(&args(line))
+    }
+
+    #[test]
+    fn parses_launch_flags() {
+        let launch_args = LaunchArgs::parse(args(&["--service", "poe", "--url=https://poe.com/chat/1", "--prompt", "hi there", "--hidden"]));
+
+        assert_eq!(launch_args.service.as_deref(), Some("poe"));
+        assert_eq!(launch_args.url.as_deref(), Some("https://poe.com/chat/1"));
+        assert_eq!(launch_args.prompt.as_deref(), Some("hi there"));
+        assert!(launch_args.hidden);
+    }
+
+    #[test]
+    fn launch_flags_default_to_unset() {
+        let launch_args = LaunchArgs::parse(args(&["--service=gemini"]));
+
+        assert_eq!(launch_args.service.as_deref(), Some("gemini"));
+        assert_eq!(launch_args.url, None);
+        assert_eq!(launch_args.prompt, None);
+        assert!(!launch_args.hidden);
+        // 参数缺少值时保持未设置
+        assert_eq!(LaunchArgs::parse(args(&["--service"])).service, None);
+    }
+
+    #[test]
+    fn ignores_unknown_launch_flags() {
+        let launch_args = LaunchArgs::parse(args(&["--verbose", "--profile=work", "--service", "poe", "stray"]));
+
+        assert_eq!(launch_args.service.as_deref(), Some("poe"));
+        assert_eq!(launch_args.url, None);
+        assert!(!launch_args.hidden);
+    }
+
+    #[test]
+    fn parses_each_subcommand() {
+        assert!(matches!(parse(&["show", "poe"]), Some(Ok(Command::Show { service })) if service == "poe"));
+        assert!(matches!(parse(&["hide"]), Some(Ok(Command::Hide))));
+        assert!(matches!(
+            parse(&["ask", "gemini", "hello", "world"]),
+            Some(Ok(Command::Ask { service, prompt })) if service == "gemini" && prompt == "hello world"
+        ));
+        assert!(matches!(
+            parse(&["open", "https://poe.com/chat/1"]),
+            Some(Ok(Command::Open { url })) if url == "https://poe.com/chat/1"
+        ));
+        assert!(matches!(
+            parse(&["export", "poe"]),
+            Some(Ok(Command::Export { service, format: ExportFormat::Markdown })) if service == "poe"
+        ));
+        assert!(matches!(parse(&["export", "poe", "--format", "json"]), Some(Ok(Command::Export { format: ExportFormat::Json, .. }))));
+        assert!(matches!(parse(&["status"]), Some(Ok(Command::Status))));
+    }
+
+    #[test]
+    fn leaves_launch_arguments_to_launch_args() {
+        assert!(parse(&[]).is_none());
+        assert!(parse(&["--service", "poe"]).is_none());
+        assert!(parse(&["restart"]).is_none());
+    }
+
+    #[test]
+    fn rejects_missing_or_extra_arguments() {
+        for line in [
+            &["show"][..],
+            &["show", "poe", "gemini"],
+            &["hide", "now"],
+            &["ask", "poe"],
+            &["ask"],
+            &["open"],
+            &["export"],
+            &["status", "--verbose"],
+        ] {
+            assert_eq!(parse(line).unwrap().unwrap_err(), usage(), "{line:?}");
+        }
+    }
+
+    #[test]
+    fn parses_export_formats() {
+        assert_eq!(parse_export_format(&[]), Ok(ExportFormat::Markdown));
+        assert_eq!(parse_export_format(&args(&["--format", "md"])), Ok(ExportFormat::Markdown));
+        assert_eq!(parse_export_format(&args(&["--format=markdown"])), Ok(ExportFormat::Markdown));
+        assert_eq!(parse_export_format(&args(&["--format=json"])), Ok(ExportFormat::Json));
+    }
+
+    #[test]
+    fn rejects_bad_export_formats() {
+        assert_eq!(
+            parse_export_format(&args(&["--format", "yaml"])),
+            Err(i18n::tr("cli-unsupported-format", &[("format", "yaml")]))
+        );
+        assert_eq!(parse_export_format(&args(&["--format"])), Err(usage()));
+        assert_eq!(parse_export_format(&args(&["json"])), Err(usage()));
+        assert_eq!(parse_export_format(&args(&["--format", "json", "extra"])), Err(usage()));
+    }
+}
@@ -0,0 +1,432 @@
+use crate::{i18n, services};
+use crate::settings::{self, Settings};
+use crate::Credentials;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Runtime};
+
+// 配置包的格式标识和版本
+const BUNDLE_FORMAT: &str = "ai-assistant-config";
+const BUNDLE_VERSION: u32 = 1;
+
+// 由密码派生密钥的参数
+const PBKDF2_ITERATIONS: u32 = 600_000;
+// 导入时接受的最大迭代次数，避免恶意配置包让导入长时间占用 CPU
+const MAX_PBKDF2_ITERATIONS: u32 = PBKDF2_ITERATIONS * 4;
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+
+// 差异中代替令牌等敏感内容显示的文本
+pub const REDACTED: &str = "******";
+
+// 定义配置包结构体
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigBundle {
+    pub format: String,
+    pub version: u32,
+    pub app_version: String,
+    pub exported_at: i64,
+    pub settings: Value,
+    // 导出时的服务列表，导入时用于检查是否与本机版本兼容
+    pub services: Vec<BundledService>,
+    // 使用密码加密的凭证和访问令牌，导出时未选择包含凭证则为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials: Option<EncryptedCredentials>,
+}
+
+// 定义配置包中加密保存的敏感内容
+#[derive(Debug, Serialize, Deserialize)]
+struct Secrets {
+    credentials: Vec<Credentials>,
+    #[serde(default)]
+    control_token: Option<String>,
+}
+
+// 定义服务信息结构体
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundledService {
+    pub id: String,
+    pub name: String,
+    pub home_url: String,
+}
+
+// 定义加密凭证结构体
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncryptedCredentials {
+    pub kdf: String,
+    pub iterations: u32,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+// 定义设置差异结构体
+#[derive(Debug, Serialize)]
+pub struct SettingChange {
+    pub field: String,
+    pub current: Value,
+    pub imported: Value,
+}
+
+// 定义导入预览结构体
+#[derive(Debug, Serialize)]
+pub struct ImportPreview {
+    // 是否已经应用
+    pub applied: bool,
+    pub changes: Vec<SettingChange>,
+    // 将被写入凭证的服务
+    pub credentials: Vec<String>,
+    // 不影响导入的提示
+    pub warnings: Vec<String>,
+}
+
+// 导出配置到文件；包含凭证时必须提供用于加密的密码
+pub fn export(app_handle: &AppHandle, path: &Path, include_secrets: bool, passphrase: Option<&str>) -> Result<(), String> {
+    let mut settings = settings::get(app_handle);
+    // 访问令牌不以明文导出，只随凭证一起加密
+    let control_token = settings.control_token.take();
+    let credentials = if include_secrets {
+        let passphrase = passphrase.filter(|p| !p.is_empty()).ok_or_else(|| i18n::tr("config-export-passphrase-required", &[]))?;
+
+        let mut all = Vec::new();
+        for service in services::SERVICES {
+            if let Some(credentials) = crate::get_credentials_from_file(service.id)? {
+                all.push(credentials);
+            }
+        }
+        let secrets = Secrets {
+            credentials: all,
+            control_token,
+        };
+        Some(encrypt(&serde_json::to_vec(&secrets).map_err(|e| e.to_string())?, passphrase)?)
+    } else {
+        None
+    };
+
+    let bundle = ConfigBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        app_version: app_handle.package_info().version.to_string(),
+        exported_at: now(),
+        settings: serde_json::to_value(&settings).map_err(|e| e.to_string())?,
+        services: services::SERVICES
+            .iter()
+            .map(|service| BundledService {
+                id: service.id.to_string(),
+                name: service.name.to_string(),
+                home_url: service.home_url.to_string(),
+            })
+            .collect(),
+        credentials,
+    };
+
+    let json = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| e.to_string())
+}
+
+// 导入配置：先完整验证配置包，`apply` 为假时只返回将要发生的变化
+pub fn import(app_handle: &AppHandle, path: &Path, passphrase: Option<&str>, apply: bool) -> Result<ImportPreview, String> {
+    let json = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let bundle: ConfigBundle = serde_json::from_str(&json).map_err(|e| i18n::tr("config-invalid-bundle", &[("error", &e.to_string())]))?;
+
+    if bundle.format != BUNDLE_FORMAT {
+        return Err(i18n::tr("config-wrong-format", &[]));
+    }
+    if bundle.version > BUNDLE_VERSION {
+        return Err(i18n::tr("config-newer-version", &[("version", &bundle.app_version)]));
+    }
+
+    let mut warnings = Vec::new();
+    for service in &bundle.services {
+        if services::find(&service.id).is_none() {
+            warnings.push(i18n::tr("config-unsupported-service", &[("service", &service.name)]));
+        }
+    }
+
+    // 验证设置，忽略本机不支持的服务的窗口模式
+    let current = settings::get(app_handle);
+    let mut imported: Settings = serde_json::from_value(bundle.settings).map_err(|e| i18n::tr("error-invalid-settings", &[("error", &e.to_string())]))?;
+    imported.window_modes.retain(|service, _| services::find(service).is_some());
+
+    // 解密并验证凭证
+    let secrets = match &bundle.credentials {
+        Some(encrypted) => {
+            let passphrase = passphrase.filter(|p| !p.is_empty()).ok_or_else(|| i18n::tr("config-import-passphrase-required", &[]))?;
+            let plaintext = decrypt(encrypted, passphrase)?;
+            let secrets: Secrets = serde_json::from_slice(&plaintext).map_err(|e| i18n::tr("config-invalid-credentials", &[("error", &e.to_string())]))?;
+            if let Some(unknown) = secrets.credentials.iter().find(|c| services::find(&c.service).is_none()) {
+                return Err(i18n::tr("error-unsupported-service", &[("service", &unknown.service)]));
+            }
+            Some(secrets)
+        }
+        None => None,
+    };
+
+    // 访问令牌只从加密内容中读取，配置包不含凭证时保留本机的访问令牌
+    let (credentials, control_token) = match secrets {
+        Some(secrets) => (secrets.credentials, secrets.control_token),
+        None => (Vec::new(), current.control_token.clone()),
+    };
+    imported.control_token = control_token;
+    imported.validate()?;
+
+    let preview = ImportPreview {
+        applied: apply,
+        changes: diff(&current, &imported)?,
+        credentials: credentials.iter().map(|c| format!("{}（{}）", c.service, c.username)).collect(),
+        warnings,
+    };
+
+    if apply {
+        let mut saved = Vec::new();
+        for credentials in &credentials {
+            saved.push((credentials.service.as_str(), crate::get_credentials_from_file(&credentials.service)?));
+        }
+        apply_import(app_handle, current, imported, || save_credentials(&credentials), || restore_credentials(&saved))?;
+    }
+
+    Ok(preview)
+}
+
+// 先应用设置再写入凭证，写入凭证失败时恢复原来的设置和凭证，避免只导入一部分
+fn apply_import<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    current: Settings,
+    imported: Settings,
+    save: impl FnOnce() -> Result<(), String>,
+    restore: impl FnOnce(),
+) -> Result<(), String> {
+    settings::update(app_handle, |settings| *settings = imported)?;
+
+    if let Err(e) = save() {
+        tracing::error!(error = %e, "导入凭证失败，恢复原来的配置");
+        restore();
+        settings::update(app_handle, |settings| *settings = current)?;
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+fn save_credentials(credentials: &[Credentials]) -> Result<(), String> {
+    for credentials in credentials {
+        crate::save_credentials_to_file(&credentials.service, &credentials.username, &credentials.password)?;
+    }
+
+    Ok(())
+}
+
+// 恢复导入前的凭证，导入前没有凭证的服务删除凭证
+fn restore_credentials(saved: &[(&str, Option<Credentials>)]) {
+    for (service, credentials) in saved {
+        let result = match credentials {
+            Some(credentials) => crate::save_credentials_to_file(service, &credentials.username, &credentials.password),
+            None => crate::delete_credentials_from_file(service),
+        };
+        crate::logging::log_err!(result, "恢复凭证失败");
+    }
+}
+
+// 比较两份设置，敏感字段只显示是否变化
+fn diff(current: &Settings, imported: &Settings) -> Result<Vec<SettingChange>, String> {
+    let current = serde_json::to_value(current).map_err(|e| e.to_string())?;
+    let imported = serde_json::to_value(imported).map_err(|e| e.to_string())?;
+    let (Value::Object(current), Value::Object(imported)) = (current, imported) else {
+        return Ok(Vec::new());
+    };
+
+    let redact = |field: &str, value: Value| {
+        if field == "control_token" && !value.is_null() {
+            json!(REDACTED)
+        } else {
+            value
+        }
+    };
+
+    Ok(imported
+        .into_iter()
+        .filter(|(field, value)| current.get(field) != Some(value))
+        .map(|(field, value)| SettingChange {
+            current: redact(&field, current.get(&field).cloned().unwrap_or(Value::Null)),
+            imported: redact(&field, value),
+            field,
+        })
+        .collect())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Result<LessSafeKey, String> {
+    let iterations = NonZeroU32::new(iterations).ok_or_else(|| i18n::tr("config-invalid-key-parameters", &[]))?;
+    let mut key = [0u8; KEY_LEN];
+    ring::pbkdf2::derive(ring::pbkdf2::PBKDF2_HMAC_SHA256, iterations, salt, passphrase.as_bytes(), &mut key);
+
+    let key = UnboundKey::new(&CHACHA20_POLY1305, &key).map_err(|_| i18n::tr("config-key-failed", &[]))?;
+    Ok(LessSafeKey::new(key))
+}
+
+// 使用由密码派生的密钥加密
+fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<EncryptedCredentials, String> {
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut salt).map_err(|_| i18n::tr("config-random-failed", &[]))?;
+    rng.fill(&mut nonce).map_err(|_| i18n::tr("config-random-failed", &[]))?;
+
+    let key = derive_key(passphrase, &salt, PBKDF2_ITERATIONS)?;
+    let mut ciphertext = plaintext.to_vec();
+    key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut ciphertext)
+        .map_err(|_| i18n::tr("config-encrypt-failed", &[]))?;
+
+    Ok(EncryptedCredentials {
+        kdf: "pbkdf2-sha256".to_string(),
+        iterations: PBKDF2_ITERATIONS,
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    })
+}
+
+fn decrypt(encrypted: &EncryptedCredentials, passphrase: &str) -> Result<Vec<u8>, String> {
+    if encrypted.kdf != "pbkdf2-sha256" {
+        return Err(i18n::tr("config-unsupported-kdf", &[("kdf", &encrypted.kdf)]));
+    }
+
+    let salt = BASE64.decode(&encrypted.salt).map_err(|e| e.to_string())?;
+    let nonce: [u8; NONCE_LEN] = BASE64
+        .decode(&encrypted.nonce)
+        .map_err(|e| e.to_string())?
+        .try_into()
+        .map_err(|_| i18n::tr("config-invalid-credential-data", &[]))?;
+    let mut ciphertext = BASE64.decode(&encrypted.ciphertext).map_err(|e| e.to_string())?;
+
+    if !(PBKDF2_ITERATIONS..=MAX_PBKDF2_ITERATIONS).contains(&encrypted.iterations) {
+        return Err(i18n::tr("config-unsupported-iterations", &[("iterations", &encrypted.iterations.to_string())]));
+    }
+
+    let key = derive_key(passphrase, &salt, encrypted.iterations)?;
+    let plaintext = key
+        .open_in_place(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut ciphertext)
+        .map_err(|_| i18n::tr("config-decrypt-failed", &[]))?;
+
+    Ok(plaintext.to_vec())
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::ThemeSetting;
+    use crate::test_support::mock_app;
+    use std::cell::Cell;
+
+    #[test]
+    fn encrypted_secrets_round_trip() {
+        let encrypted = encrypt(b"secret credentials", "correct horse").unwrap();
+        assert_eq!(encrypted.kdf, "pbkdf2-sha256");
+        assert_eq!(encrypted.iterations, PBKDF2_ITERATIONS);
+        assert!(!encrypted.ciphertext.contains("secret"));
+
+        assert_eq!(decrypt(&encrypted, "correct horse").unwrap(), b"secret credentials");
+        assert_eq!(decrypt(&encrypted, "wrong horse").unwrap_err(), i18n::tr("config-decrypt-failed", &[]));
+    }
+
+    #[test]
+    fn rejects_tampered_ciphertext() {
+        let mut encrypted = encrypt(b"secret", "passphrase").unwrap();
+        let mut ciphertext = BASE64.decode(&encrypted.ciphertext).unwrap();
+        ciphertext[0] ^= 1;
+        encrypted.ciphertext = BASE64.encode(ciphertext);
+
+        assert_eq!(decrypt(&encrypted, "passphrase").unwrap_err(), i18n::tr("config-decrypt-failed", &[]));
+    }
+
+    #[test]
+    fn bounds_pbkdf2_iterations() {
+        let encrypted = |iterations: u32| EncryptedCredentials {
+            kdf: "pbkdf2-sha256".to_string(),
+            iterations,
+            salt: BASE64.encode([0u8; SALT_LEN]),
+            nonce: BASE64.encode([0u8; NONCE_LEN]),
+            ciphertext: BASE64.encode([0u8; 32]),
+        };
+
+        for iterations in [0, 1, PBKDF2_ITERATIONS - 1, MAX_PBKDF2_ITERATIONS + 1, u32::MAX] {
+            assert_eq!(
+                decrypt(&encrypted(iterations), "passphrase").unwrap_err(),
+                i18n::tr("config-unsupported-iterations", &[("iterations", &iterations.to_string())])
+            );
+        }
+        // 范围内的迭代次数会继续派生密钥，因为数据是伪造的，最后解密失败
+        assert_eq!(decrypt(&encrypted(PBKDF2_ITERATIONS), "passphrase").unwrap_err(), i18n::tr("config-decrypt-failed", &[]));
+    }
+
+    #[test]
+    fn diff_redacts_the_control_token() {
+        let current = Settings {
+            control_token: Some("old-token".to_string()),
+            ..Settings::default()
+        };
+        let imported = Settings {
+            control_token: Some("new-token".to_string()),
+            theme: ThemeSetting::Dark,
+            ..Settings::default()
+        };
+
+        let changes = diff(&current, &imported).unwrap();
+        assert_eq!(changes.len(), 2);
+        let token = changes.iter().find(|c| c.field == "control_token").unwrap();
+        assert_eq!((&token.current, &token.imported), (&json!(REDACTED), &json!(REDACTED)));
+        let theme = changes.iter().find(|c| c.field == "theme").unwrap();
+        assert_eq!((&theme.current, &theme.imported), (&json!("system"), &json!("dark")));
+
+        // 删除令牌时显示为空
+        let removed = diff(&current, &Settings::default()).unwrap();
+        assert_eq!((&removed[0].current, &removed[0].imported), (&json!(REDACTED), &Value::Null));
+        assert!(!serde_json::to_string(&removed).unwrap().contains("old-token"));
+    }
+
+    #[test]
+    fn failed_imports_restore_settings_and_credentials() {
+        let app = mock_app();
+        let current = settings::get(app.handle());
+        let imported = Settings {
+            theme: ThemeSetting::Dark,
+            restore_last_url: false,
+            ..current.clone()
+        };
+        let restored = Cell::new(false);
+
+        let result = apply_import(app.handle(), current.clone(), imported, || Err("disk full".to_string()), || restored.set(true));
+
+        assert_eq!(result, Err("disk full".to_string()));
+        assert!(restored.get());
+        assert_eq!(settings::get(app.handle()), current);
+    }
+
+    #[test]
+    fn successful_imports_keep_the_new_settings() {
+        let app = mock_app();
+        let current = settings::get(app.handle());
+        let imported = Settings {
+            theme: ThemeSetting::Dark,
+            ..current.clone()
+        };
+
+        apply_import(app.handle(), current, imported.clone(), || Ok(()), || panic!("nothing to restore")).unwrap();
+
+        assert_eq!(settings::get(app.handle()), imported);
+    }
+}
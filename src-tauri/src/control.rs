@@ -0,0 +1,223 @@
+use crate::app_state::StateStore;
+use crate::archive::{Archive, Conversation};
+use crate::cli::{Command, ExportFormat, LaunchArgs};
+use crate::{i18n, logging, prompt, services, settings, tray, window_state};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+
+// 定义服务状态结构体
+#[derive(Debug, Serialize)]
+pub struct ServiceStatus {
+    pub id: &'static str,
+    pub name: &'static str,
+    // 窗口是否已创建
+    pub open: bool,
+    pub visible: bool,
+    pub focused: bool,
+    pub url: Option<String>,
+    // 是否正在生成回答
+    pub generating: bool,
+    // 是否有未查看的回答
+    pub pending_response: bool,
+}
+
+// 定义应用状态快照结构体
+#[derive(Debug, Serialize)]
+pub struct Status {
+    pub version: String,
+    pub active_service: String,
+    pub services: Vec<ServiceStatus>,
+}
+
+// 检查控制令牌：未设置令牌时不要求令牌
+pub fn authorize<R: Runtime>(app_handle: &AppHandle<R>, token: Option<&str>) -> bool {
+    match settings::get(app_handle).control_token {
+        None => true,
+        Some(expected) => token.is_some_and(|token| constant_time_eq(&expected, token)),
+    }
+}
+
+// 比较令牌时耗时与内容无关，避免通过响应时间猜测令牌
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// 执行命令行客户端发送的子命令，返回要输出的 JSON 结果
+pub fn execute<R: Runtime>(app_handle: &AppHandle<R>, command: Command) -> Result<Value, String> {
+    let app_handle = app_handle.clone();
+    match command {
+        Command::Show { service } => {
+            tauri::async_runtime::block_on(show_service(app_handle, service.clone()))?;
+            Ok(json!({ "service": service }))
+        }
+        Command::Hide => Ok(json!({ "hidden": hide_windows(app_handle) })),
+        Command::Ask { service, prompt } => {
+            tauri::async_runtime::block_on(send_prompt(app_handle, service.clone(), prompt, None))?;
+            Ok(json!({ "service": service }))
+        }
+        Command::Open { url } => open_url(app_handle, url),
+        Command::Export { service, format } => {
+            tauri::async_runtime::block_on(export_conversation(app_handle, service, Some(format)))
+        }
+        Command::Status => serde_json::to_value(get_status(app_handle)).map_err(|e| e.to_string()),
+    }
+}
+
+// 定义命令：获取各服务窗口的状态
+#[tauri::command]
+pub fn list_services<R: Runtime>(app_handle: AppHandle<R>) -> Vec<ServiceStatus> {
+    get_status(app_handle).services
+}
+
+// 定义命令：获取应用和各服务窗口的状态
+#[tauri::command]
+pub fn get_status<R: Runtime>(app_handle: AppHandle<R>) -> Status {
+    let state = app_handle.state::<StateStore>().snapshot();
+
+    let services = services::SERVICES
+        .iter()
+        .map(|service| {
+            let window = app_handle.get_webview_window(service.id);
+            ServiceStatus {
+                id: service.id,
+                name: service.name,
+                open: window.is_some(),
+                visible: window.as_ref().is_some_and(|w| w.is_visible().unwrap_or(false)),
+                focused: window.as_ref().is_some_and(|w| w.is_focused().unwrap_or(false)),
+                url: window.as_ref().and_then(|w| w.url().ok()).map(String::from),
+                generating: state.generating_services.iter().any(|s| s == service.id),
+                pending_response: state.pending_responses.iter().any(|s| s == service.id),
+            }
+        })
+        .collect();
+
+    Status {
+        version: app_handle.package_info().version.to_string(),
+        active_service: crate::last_active_service(&app_handle),
+        services,
+    }
+}
+
+// 定义命令：显示服务窗口（会创建窗口，因此是异步命令，不在主线程上执行）
+#[tauri::command]
+pub async fn show_service<R: Runtime>(app_handle: AppHandle<R>, service: String) -> Result<(), String> {
+    show(&app_handle, &service).map(|_| ())
+}
+
+// 定义命令：隐藏所有可见的窗口，返回被隐藏的窗口标签
+#[tauri::command]
+pub fn hide_windows<R: Runtime>(app_handle: AppHandle<R>) -> Vec<String> {
+    let mut hidden = Vec::new();
+
+    for (label, window) in app_handle.webview_windows() {
+        if window.is_visible().unwrap_or(false) && window_state::hide(&window).is_ok() {
+            hidden.push(label);
+        }
+    }
+
+    logging::log_err!(tray::refresh(&app_handle), "更新托盘失败");
+
+    hidden
+}
+
+// 定义命令：显示服务窗口并填入提示词，默认直接发送
+#[tauri::command]
+pub async fn send_prompt<R: Runtime>(
+    app_handle: AppHandle<R>,
+    service: String,
+    prompt: String,
+    submit: Option<bool>,
+) -> Result<(), String> {
+    let window = show(&app_handle, &service)?;
+
+    prompt::fill(&window, &prompt, submit.unwrap_or(true))
+}
+
+// 定义命令：获取服务最近一次生成的回答
+#[tauri::command]
+pub fn get_last_response<R: Runtime>(app_handle: AppHandle<R>, service: String) -> Result<Option<String>, String> {
+    services::find(&service).ok_or_else(|| i18n::tr("error-unsupported-service", &[("service", &service)]))?;
+
+    Ok(app_handle.state::<StateStore>().snapshot().last_responses.remove(&service))
+}
+
+// 定义命令：打开 URL，服务的页面在对应窗口中打开，其他页面使用系统浏览器打开
+#[tauri::command]
+pub fn open_url<R: Runtime>(app_handle: AppHandle<R>, url: String) -> Result<Value, String> {
+    let parsed = tauri::Url::parse(&url).map_err(|e| e.to_string())?;
+    let owner = services::SERVICES.iter().find(|s| s.owns_url(&parsed)).map(|s| s.id);
+
+    let args = LaunchArgs {
+        url: Some(url),
+        ..LaunchArgs::default()
+    };
+    let app_handle_clone = app_handle.clone();
+    app_handle
+        .run_on_main_thread(move || {
+            crate::handle_launch_args(&app_handle_clone, &args);
+        })
+        .map_err(|e| e.to_string())?;
+
+    Ok(json!({ "url": parsed.as_str(), "service": owner }))
+}
+
+// 定义命令：导出服务当前页面的对话，默认导出为 Markdown
+#[tauri::command]
+pub async fn export_conversation<R: Runtime>(
+    app_handle: AppHandle<R>,
+    service: String,
+    format: Option<ExportFormat>,
+) -> Result<Value, String> {
+    // 需要等待页面归档对话，在阻塞线程上执行
+    tauri::async_runtime::spawn_blocking(move || export(&app_handle, &service, format.unwrap_or_default()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+// 显示服务窗口
+fn show<R: Runtime>(app_handle: &AppHandle<R>, service: &str) -> Result<tauri::WebviewWindow<R>, String> {
+    services::find(service).ok_or_else(|| i18n::tr("error-unsupported-service", &[("service", service)]))?;
+
+    crate::show_service_window(app_handle, service).ok_or_else(|| i18n::tr("error-open-service-window", &[("service", service)]))
+}
+
+// 导出服务当前页面的对话
+fn export<R: Runtime>(app_handle: &AppHandle<R>, service: &str, format: ExportFormat) -> Result<Value, String> {
+    services::find(service).ok_or_else(|| i18n::tr("error-unsupported-service", &[("service", service)]))?;
+    let window = app_handle
+        .get_webview_window(service)
+        .ok_or_else(|| i18n::tr("error-service-window-closed", &[("service", service)]))?;
+    let url = window.url().map_err(|e| e.to_string())?;
+
+    // 先让对话提取脚本立即归档当前页面，再从归档中读取
+    logging::log_err!(window.eval("window.__aiArchiveConversation && window.__aiArchiveConversation();"), "触发对话归档失败");
+    std::thread::sleep(Duration::from_secs(1));
+
+    let conversation = app_handle
+        .state::<Archive>()
+        .conversation(service, url.as_str())?
+        .ok_or_else(|| i18n::tr("error-no-conversation", &[]))?;
+
+    match format {
+        ExportFormat::Markdown => Ok(json!({
+            "service": service,
+            "url": conversation.url,
+            "format": "md",
+            "content": to_markdown(&conversation),
+        })),
+        ExportFormat::Json => serde_json::to_value(conversation).map_err(|e| e.to_string()),
+    }
+}
+
+// 将对话转换为 Markdown 文档
+fn to_markdown(conversation: &Conversation) -> String {
+    let title = if conversation.title.is_empty() {
+        services::find(&conversation.service).map_or(conversation.service.as_str(), |s| s.name)
+    } else {
+        conversation.title.as_str()
+    };
+
+    format!("# {}\n\n<{}>\n\n{}\n", title, conversation.url, conversation.content)
+}
@@ -0,0 +1,93 @@
+// 对话提取脚本
+// 从页面中提取当前对话，并在内容变化后交给 Rust 归档到本地历史记录
+
+(function() {
+    // 避免重复注入
+    if (window.__aiConversationExtractor) {
+        return;
+    }
+    window.__aiConversationExtractor = true;
+
+    const SERVICE = '__SERVICE__';
+
+    // 各服务的对话结构选择器
+    const SELECTORS = {
+        gemini: {
+            turns: 'user-query, model-response',
+            user: 'user-query',
+            text: '.query-text, message-content'
+        },
+        poe: {
+            turns: '[class*="ChatMessage_chatMessage"]',
+            user: '[class*="Message_rightSideMessageWrapper"]',
+            text: '[class*="Markdown_markdownContainer"]'
+        }
+    };
+
+    // 提取当前页面的对话内容
+    function extractConversation() {
+        const selectors = SELECTORS[SERVICE];
+        if (!selectors) {
+            return null;
+        }
+
+        const parts = [];
+        document.querySelectorAll(selectors.turns).forEach(turn => {
+            const textNode = turn.querySelector(selectors.text) || turn;
+            const text = (textNode.innerText || '').trim();
+            if (!text) {
+                return;
+            }
+
+            const isUser = turn.matches(selectors.user) || turn.querySelector(selectors.user);
+            parts.push((isUser ? '## User\n\n' : '## Assistant\n\n') + text);
+        });
+
+        if (parts.length === 0) {
+            return null;
+        }
+
+        return {
+            url: location.href,
+            title: (document.title || '').trim(),
+            content: parts.join('\n\n')
+        };
+    }
+
+    let lastSnapshot = '';
+    let timer = null;
+
+    // 将对话发送给 Rust 归档
+    function archiveConversation() {
+        const ipc = window.__TAURI_INTERNALS__;
+        const conversation = extractConversation();
+        if (!ipc || !conversation) {
+            return;
+        }
+
+        const snapshot = conversation.url + '\n' + conversation.content;
+        if (snapshot === lastSnapshot) {
+            return;
+        }
+        lastSnapshot = snapshot;
+
+        ipc.invoke('archive_conversation', conversation).catch(error => {
+            console.error('归档对话时出错:', error);
+        });
+    }
+
+    // 页面内容变化后延迟归档，等待流式输出稳定
+    const observer = new MutationObserver(() => {
+        clearTimeout(timer);
+        timer = setTimeout(archiveConversation, 5000);
+    });
+    observer.observe(document.body, { childList: true, subtree: true, characterData: true });
+
+    window.addEventListener('beforeunload', archiveConversation);
+
+    // 允许 Rust 在导出前立即归档，无需等待延迟
+    window.__aiArchiveConversation = archiveConversation;
+    archiveConversation();
+
+    console.log('Conversation extractor loaded');
+})();
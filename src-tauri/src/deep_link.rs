@@ -0,0 +1,139 @@
+use crate::cli::LaunchArgs;
+use crate::{i18n, services};
+
+// 深度链接协议名称
+pub const SCHEME: &str = "aiassistant";
+
+// 链接中提示词的最大长度（字符数）
+const MAX_PROMPT_CHARS: usize = 10_000;
+
+// 判断参数是否是本应用的深度链接
+pub fn is_deep_link(arg: &str) -> bool {
+    arg.get(..SCHEME.len() + 1)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case(&format!("{}:", SCHEME)))
+}
+
+// 解析深度链接，支持：
+// - `aiassistant://open?service=poe`
+// - `aiassistant://ask?service=gemini&q=...`
+// - `aiassistant://conversation?url=...`
+pub fn parse(link: &str) -> Result<LaunchArgs, String> {
+    let url = tauri::Url::parse(link).map_err(|e| i18n::tr("link-invalid", &[("error", &e.to_string())]))?;
+    if url.scheme() != SCHEME {
+        return Err(i18n::tr("link-unsupported-scheme", &[("scheme", url.scheme())]));
+    }
+
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+            .filter(|value| !value.is_empty())
+    };
+
+    match url.host_str().unwrap_or_default() {
+        "open" => {
+            let service = param("service").ok_or_else(|| i18n::tr("link-missing-param", &[("name", "service")]))?;
+            Ok(LaunchArgs {
+                service: Some(validate_service(service)?),
+                ..LaunchArgs::default()
+            })
+        }
+        "ask" => {
+            let prompt = param("q").ok_or_else(|| i18n::tr("link-missing-param", &[("name", "q")]))?;
+            if prompt.chars().count() > MAX_PROMPT_CHARS {
+                return Err(i18n::tr("link-prompt-too-long", &[("max", &MAX_PROMPT_CHARS.to_string())]));
+            }
+
+            Ok(LaunchArgs {
+                service: param("service").map(validate_service).transpose()?,
+                prompt: Some(prompt),
+                ..LaunchArgs::default()
+            })
+        }
+        "conversation" => {
+            let target = param("url").ok_or_else(|| i18n::tr("link-missing-param", &[("name", "url")]))?;
+            let target = tauri::Url::parse(&target).map_err(|e| i18n::tr("link-invalid-conversation", &[("error", &e.to_string())]))?;
+
+            // 只允许打开服务自己的页面，避免链接被用来打开任意网站
+            let service = services::SERVICES
+                .iter()
+                .find(|s| s.owns_url(&target))
+                .ok_or_else(|| i18n::tr("link-unknown-conversation", &[("url", target.as_str())]))?;
+
+            Ok(LaunchArgs {
+                service: Some(service.id.to_string()),
+                url: Some(target.to_string()),
+                ..LaunchArgs::default()
+            })
+        }
+        action => Err(i18n::tr("link-unsupported-action", &[("action", action)])),
+    }
+}
+
+fn validate_service(service: String) -> Result<String, String> {
+    match services::find(&service) {
+        Some(_) => Ok(service),
+        None => Err(i18n::tr("error-unsupported-service", &[("service", &service)])),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_valid_links() {
+        let open = parse("aiassistant://open?service=poe").unwrap();
+        assert_eq!(open.service.as_deref(), Some("poe"));
+        assert_eq!(open.prompt, None);
+
+        let ask = parse("aiassistant://ask?service=gemini&q=hello%20world").unwrap();
+        assert_eq!(ask.service.as_deref(), Some("gemini"));
+        assert_eq!(ask.prompt.as_deref(), Some("hello world"));
+
+        let conversation = parse("aiassistant://conversation?url=https%3A%2F%2Fpoe.com%2Fchat%2Fabc").unwrap();
+        assert_eq!(conversation.service.as_deref(), Some("poe"));
+        assert_eq!(conversation.url.as_deref(), Some("https://poe.com/chat/abc"));
+        assert!(is_deep_link("AIAssistant://open?service=poe"));
+    }
+
+    #[test]
+    fn rejects_other_schemes() {
+        assert!(!is_deep_link("https://poe.com"));
+        assert_eq!(
+            parse("https://open?service=poe").unwrap_err(),
+            i18n::tr("link-unsupported-scheme", &[("scheme", "https")])
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_services() {
+        for link in ["aiassistant://open?service=chatgpt", "aiassistant://ask?service=chatgpt&q=hi"] {
+            assert_eq!(parse(link).unwrap_err(), i18n::tr("error-unsupported-service", &[("service", "chatgpt")]));
+        }
+        assert_eq!(
+            parse("aiassistant://open").unwrap_err(),
+            i18n::tr("link-missing-param", &[("name", "service")])
+        );
+    }
+
+    #[test]
+    fn rejects_conversations_no_service_owns() {
+        for target in ["https://example.com/chat/abc", "http://poe.com/chat/abc", "https://accounts.google.com/signin"] {
+            let link = format!("aiassistant://conversation?url={}", target);
+            let url = tauri::Url::parse(target).unwrap();
+            assert_eq!(
+                parse(&link).unwrap_err(),
+                i18n::tr("link-unknown-conversation", &[("url", url.as_str())]),
+                "{target}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_prompts_over_the_limit() {
+        let link = format!("aiassistant://ask?q={}", "a".repeat(MAX_PROMPT_CHARS + 1));
+        assert!(parse(&link).is_err());
+        assert!(parse(&format!("aiassistant://ask?q={}", "a".repeat(MAX_PROMPT_CHARS))).is_ok());
+    }
+}
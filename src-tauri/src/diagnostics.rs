@@ -0,0 +1,273 @@
+use crate::config_bundle::REDACTED;
+use crate::error::AppError;
+use crate::window_state::{self, WindowState};
+use crate::{i18n, logging, services, session, settings};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+// 在 main() 中注册的插件，需要与其保持一致
+const PLUGINS: &[&str] = &["http", "store", "deep-link"];
+
+// 定义诊断报告结构体，报告中不包含密码和令牌
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub generated_at: i64,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub locale: String,
+    pub webview: WebviewInfo,
+    pub settings: Value,
+    pub services: Vec<ServiceDiagnostics>,
+    pub credentials: CredentialStatus,
+    pub windows: Vec<WindowDiagnostics>,
+    pub plugins: Vec<String>,
+    pub logs: Vec<String>,
+}
+
+// 定义网页引擎信息结构体
+#[derive(Debug, Serialize)]
+pub struct WebviewInfo {
+    pub engine: String,
+    pub version: Option<String>,
+}
+
+// 定义服务的实际配置
+#[derive(Debug, Serialize)]
+pub struct ServiceDiagnostics {
+    pub id: String,
+    // 启动时将打开的页面，只包含来源，不包含对话路径
+    pub url: String,
+    pub user_agent: String,
+    pub browser_args: String,
+    pub profile_dir: Option<String>,
+}
+
+// 定义凭证存储状态结构体
+#[derive(Debug, Serialize)]
+pub struct CredentialStatus {
+    pub backend: String,
+    pub location: String,
+    pub services: Vec<ServiceCredentialStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ServiceCredentialStatus {
+    pub service: String,
+    pub stored: bool,
+    // 读取凭证失败时的错误
+    pub error: Option<AppError>,
+}
+
+// 定义窗口状态结构体
+#[derive(Debug, Serialize)]
+pub struct WindowDiagnostics {
+    pub label: String,
+    pub open: bool,
+    pub visible: bool,
+    pub focused: bool,
+    pub minimized: bool,
+    pub maximized: bool,
+    // 上次保存的窗口状态
+    pub saved: Option<WindowState>,
+}
+
+// 收集诊断信息，`log_lines` 为附带的最近日志行数
+pub fn collect(app_handle: &AppHandle, log_lines: usize) -> Result<Report, String> {
+    let mut settings = settings::get(app_handle);
+    if settings.control_token.is_some() {
+        settings.control_token = Some(REDACTED.to_string());
+    }
+
+    // 服务窗口使用应用本地数据目录中的网页数据
+    let profile_dir = app_handle
+        .path()
+        .app_local_data_dir()
+        .ok()
+        .map(|dir| dir.display().to_string());
+
+    let services = services::SERVICES
+        .iter()
+        .map(|service| ServiceDiagnostics {
+            id: service.id.to_string(),
+            url: session::restore_url(app_handle, service.id)
+                .map(|url| url.origin().ascii_serialization())
+                .unwrap_or_else(|| service.home_url.to_string()),
+            user_agent: services::USER_AGENT.to_string(),
+            browser_args: services::BROWSER_ARGS.to_string(),
+            profile_dir: profile_dir.clone(),
+        })
+        .collect();
+
+    // 只报告是否保存了凭证，不包含用户名和密码
+    let credentials = CredentialStatus {
+        backend: "file".to_string(),
+        location: std::env::current_dir()
+            .map(|dir| dir.join("credentials").display().to_string())
+            .unwrap_or_else(|_| "credentials".to_string()),
+        services: services::SERVICES
+            .iter()
+            .map(|service| {
+                let result = crate::get_credentials_from_file(service.id);
+                ServiceCredentialStatus {
+                    service: service.id.to_string(),
+                    stored: matches!(result, Ok(Some(_))),
+                    error: result.err(),
+                }
+            })
+            .collect(),
+    };
+
+    let mut labels: Vec<String> = services::SERVICES.iter().map(|s| s.id.to_string()).collect();
+    labels.push("settings".to_string());
+    let windows = labels
+        .into_iter()
+        .map(|label| {
+            let window = app_handle.get_webview_window(&label);
+            let query = |f: fn(&tauri::WebviewWindow) -> tauri::Result<bool>| {
+                window.as_ref().is_some_and(|w| f(w).unwrap_or(false))
+            };
+            WindowDiagnostics {
+                open: window.is_some(),
+                visible: query(tauri::WebviewWindow::is_visible),
+                focused: query(tauri::WebviewWindow::is_focused),
+                minimized: query(tauri::WebviewWindow::is_minimized),
+                maximized: query(tauri::WebviewWindow::is_maximized),
+                saved: window_state::load(app_handle, &label),
+                label,
+            }
+        })
+        .collect();
+
+    Ok(Report {
+        generated_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0),
+        app_version: app_handle.package_info().version.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        locale: i18n::current(app_handle).tag().to_string(),
+        webview: WebviewInfo {
+            engine: webview_engine().to_string(),
+            version: tauri::webview_version().ok(),
+        },
+        settings: serde_json::to_value(&settings).map_err(|e| e.to_string())?,
+        services,
+        credentials,
+        windows,
+        plugins: PLUGINS.iter().map(|p| p.to_string()).collect(),
+        logs: logging::recent_lines(log_lines)?.iter().map(|line| scrub(line)).collect(),
+    })
+}
+
+// 将诊断报告保存为 JSON 文件，便于附加到问题报告中
+pub fn save(report: &Report, path: &Path) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(report).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| e.to_string())
+}
+
+// 清除日志行中可能包含的提示词和 URL 查询参数
+fn scrub(line: &str) -> String {
+    redact_prompts(&strip_queries(line))
+}
+
+// 去掉 URL 的查询字符串和片段，深层链接的提示词也在查询字符串中
+fn strip_queries(line: &str) -> String {
+    let mut scrubbed = String::with_capacity(line.len());
+    let mut rest = line;
+
+    while let Some(start) = rest.find("://") {
+        let url = &rest[start..];
+        let end = url
+            .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>'))
+            .unwrap_or(url.len());
+        scrubbed.push_str(&rest[..start]);
+        match url[..end].find(['?', '#']) {
+            Some(query) => {
+                scrubbed.push_str(&url[..query]);
+                scrubbed.push('?');
+                scrubbed.push_str(REDACTED);
+            }
+            None => scrubbed.push_str(&url[..end]),
+        }
+        rest = &url[end..];
+    }
+    scrubbed.push_str(rest);
+
+    scrubbed
+}
+
+// 替换 `prompt=` 字段的值，值可以带引号
+fn redact_prompts(line: &str) -> String {
+    const FIELD: &str = "prompt=";
+
+    let mut scrubbed = String::with_capacity(line.len());
+    let mut rest = line;
+
+    while let Some(start) = rest.find(FIELD) {
+        let value = &rest[start + FIELD.len()..];
+        scrubbed.push_str(&rest[..start + FIELD.len()]);
+        let end = if let Some(quoted) = value.strip_prefix('"') {
+            // 跳过转义的引号，找不到结束的引号时替换到行尾
+            let mut escaped = false;
+            quoted
+                .char_indices()
+                .find(|&(_, c)| {
+                    let end = c == '"' && !escaped;
+                    escaped = c == '\\' && !escaped;
+                    end
+                })
+                .map_or(value.len(), |(i, _)| i + 2)
+        } else {
+            value.find(|c: char| c.is_whitespace() || c == '&').unwrap_or(value.len())
+        };
+        scrubbed.push_str(REDACTED);
+        rest = &value[end..];
+    }
+    scrubbed.push_str(rest);
+
+    scrubbed
+}
+
+fn webview_engine() -> &'static str {
+    if cfg!(windows) {
+        "WebView2"
+    } else if cfg!(target_os = "macos") {
+        "WKWebView"
+    } else {
+        "WebKitGTK"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scrub_strips_url_queries() {
+        let scheme = crate::deep_link::SCHEME;
+        assert_eq!(
+            scrub(&format!("WARN 打开链接失败 error={scheme}://ask?service=poe&prompt=secret%20text done")),
+            format!("WARN 打开链接失败 error={scheme}://ask?****** done")
+        );
+        assert_eq!(
+            scrub(r#"url="https://poe.com/chat/abc#top" https://gemini.google.com/app"#),
+            r#"url="https://poe.com/chat/abc?******" https://gemini.google.com/app"#
+        );
+    }
+
+    #[test]
+    fn scrub_redacts_prompt_fields() {
+        assert_eq!(scrub("INFO 发送提示词 prompt=hello service=poe"), "INFO 发送提示词 prompt=****** service=poe");
+        assert_eq!(
+            scrub(r#"INFO 发送提示词 prompt="say \"hi\" twice" service=poe"#),
+            "INFO 发送提示词 prompt=****** service=poe"
+        );
+        assert_eq!(scrub(r#"prompt="unterminated"#), "prompt=******");
+    }
+}
@@ -0,0 +1,149 @@
+use crate::i18n;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::{json, Value};
+use std::fmt;
+use std::io;
+
+// 定义命令返回的错误，前端根据 `code` 区分错误类型，
+// 序列化为 `{ "code": "...", "message": "...", "details": {...} }`
+#[derive(Debug)]
+pub enum AppError {
+    // 不支持的服务
+    UnknownService { service: String },
+    // 文件不存在
+    NotFound { path: String },
+    // 找不到浏览器模拟脚本
+    BrowserEmulationMissing { path: String },
+    // 读写文件失败
+    Io { path: String, message: String },
+    // 文件内容无法解析
+    InvalidData { path: String, message: String },
+    // 在网页中执行脚本失败
+    Webview { message: String },
+}
+
+impl AppError {
+    // 由读写文件的错误创建，区分文件不存在和其他错误
+    pub fn io(path: &str, error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::NotFound => AppError::NotFound { path: path.to_string() },
+            _ => AppError::Io {
+                path: path.to_string(),
+                message: error.to_string(),
+            },
+        }
+    }
+
+    pub fn webview(error: impl fmt::Display) -> Self {
+        AppError::Webview { message: error.to_string() }
+    }
+
+    // 稳定的错误码，修改会影响前端和控制接口的调用方
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::UnknownService { .. } => "unknown_service",
+            AppError::NotFound { .. } | AppError::BrowserEmulationMissing { .. } => "not_found",
+            AppError::Io { .. } => "io",
+            AppError::InvalidData { .. } => "invalid_data",
+            AppError::Webview { .. } => "webview",
+        }
+    }
+
+    // 错误的结构化信息
+    pub fn details(&self) -> Value {
+        match self {
+            AppError::UnknownService { service } => json!({ "service": service }),
+            AppError::NotFound { path } | AppError::BrowserEmulationMissing { path } => json!({ "path": path }),
+            AppError::Io { path, .. } | AppError::InvalidData { path, .. } => json!({ "path": path }),
+            AppError::Webview { .. } => json!({}),
+        }
+    }
+}
+
+// 错误信息使用当前界面语言
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            AppError::UnknownService { service } => i18n::tr("error-unsupported-service", &[("service", service)]),
+            AppError::NotFound { path } => i18n::tr("error-not-found", &[("path", path)]),
+            AppError::BrowserEmulationMissing { path } => i18n::tr("error-browser-emulation-missing", &[("path", path)]),
+            AppError::Io { path, message } => i18n::tr("error-io", &[("path", path), ("message", message)]),
+            AppError::InvalidData { path, message } => i18n::tr("error-invalid-data", &[("path", path), ("message", message)]),
+            AppError::Webview { message } => i18n::tr("error-webview", &[("message", message)]),
+        };
+
+        f.write_str(&message)
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+// 尚未改用 AppError 的函数仍然返回字符串错误
+impl From<AppError> for String {
+    fn from(error: AppError) -> Self {
+        error.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variants() -> Vec<(AppError, &'static str, Value)> {
+        let path = "/tmp/credentials.json".to_string();
+        vec![
+            (AppError::UnknownService { service: "chatgpt".to_string() }, "unknown_service", json!({ "service": "chatgpt" })),
+            (AppError::NotFound { path: path.clone() }, "not_found", json!({ "path": path })),
+            (AppError::BrowserEmulationMissing { path: path.clone() }, "not_found", json!({ "path": path })),
+            (
+                AppError::Io { path: path.clone(), message: "permission denied".to_string() },
+                "io",
+                json!({ "path": path }),
+            ),
+            (
+                AppError::InvalidData { path: path.clone(), message: "expected value".to_string() },
+                "invalid_data",
+                json!({ "path": path }),
+            ),
+            (AppError::Webview { message: "closed".to_string() }, "webview", json!({})),
+        ]
+    }
+
+    #[test]
+    fn codes_are_stable() {
+        for (error, code, details) in variants() {
+            assert_eq!(error.code(), code, "{error:?}");
+            assert_eq!(error.details(), details, "{error:?}");
+        }
+    }
+
+    #[test]
+    fn serializes_code_message_and_details() {
+        for (error, code, details) in variants() {
+            let value = serde_json::to_value(&error).unwrap();
+            let message = error.to_string();
+
+            assert_eq!(value, json!({ "code": code, "message": message, "details": details }));
+        }
+    }
+
+    #[test]
+    fn io_errors_distinguish_missing_files() {
+        let missing = AppError::io("/tmp/a", io::Error::new(io::ErrorKind::NotFound, "gone"));
+        assert_eq!(missing.code(), "not_found");
+
+        let denied = AppError::io("/tmp/a", io::Error::new(io::ErrorKind::PermissionDenied, "denied"));
+        assert_eq!(denied.code(), "io");
+        assert!(denied.to_string().contains("/tmp/a"));
+    }
+}
@@ -0,0 +1,68 @@
+// 生成监视脚本
+// 检测服务开始和结束流式输出回答，并通知 Rust，以便窗口隐藏时发出桌面通知
+
+(function() {
+    // 避免重复注入
+    if (window.__aiGenerationWatcher) {
+        return;
+    }
+    window.__aiGenerationWatcher = true;
+
+    const SERVICE = '__SERVICE__';
+
+    // 各服务的生成状态选择器：生成过程中会出现“停止”按钮
+    const SELECTORS = {
+        gemini: {
+            stop: 'button[aria-label="Stop response"], button.send-button.stop',
+            response: 'model-response message-content'
+        },
+        poe: {
+            stop: 'button[class*="ChatStopMessageButton"]',
+            response: '[class*="Markdown_markdownContainer"]'
+        }
+    };
+
+    const selectors = SELECTORS[SERVICE];
+    if (!selectors) {
+        return;
+    }
+
+    let generating = false;
+
+    // 获取最后一条回答的文本
+    function lastResponseText() {
+        const responses = document.querySelectorAll(selectors.response);
+        const last = responses[responses.length - 1];
+        return last ? (last.innerText || '').trim() : '';
+    }
+
+    function notifyRust(command, args) {
+        const ipc = window.__TAURI_INTERNALS__;
+        if (!ipc) {
+            return;
+        }
+
+        ipc.invoke(command, args).catch(error => {
+            console.error('通知生成状态时出错:', error);
+        });
+    }
+
+    function checkGeneration() {
+        const isGenerating = document.querySelector(selectors.stop) !== null;
+        if (isGenerating === generating) {
+            return;
+        }
+        generating = isGenerating;
+
+        if (generating) {
+            notifyRust('generation_started', {});
+        } else {
+            notifyRust('generation_finished', { response: lastResponseText() });
+        }
+    }
+
+    const observer = new MutationObserver(checkGeneration);
+    observer.observe(document.body, { childList: true, subtree: true, attributes: true });
+
+    console.log('Generation watcher loaded');
+})();
@@ -0,0 +1,205 @@
+use crate::settings::{self, Language};
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{OnceLock, PoisonError, RwLock};
+use tauri::{AppHandle, Manager, Runtime};
+use unic_langid::LanguageIdentifier;
+
+// 界面实际使用的语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    ZhCn,
+}
+
+impl Locale {
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::ZhCn => "zh-CN",
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Locale::En => include_str!("locales/en.ftl"),
+            Locale::ZhCn => include_str!("locales/zh-CN.ftl"),
+        }
+    }
+}
+
+struct Bundles {
+    en: FluentBundle<FluentResource>,
+    zh_cn: FluentBundle<FluentResource>,
+}
+
+// 消息目录在首次使用时加载
+fn bundles() -> &'static Bundles {
+    static BUNDLES: OnceLock<Bundles> = OnceLock::new();
+    BUNDLES.get_or_init(|| Bundles {
+        en: load_bundle(Locale::En),
+        zh_cn: load_bundle(Locale::ZhCn),
+    })
+}
+
+fn load_bundle(locale: Locale) -> FluentBundle<FluentResource> {
+    let langid: LanguageIdentifier = locale.tag().parse().expect("Invalid language tag");
+    let resource = FluentResource::try_new(locale.source().to_string()).expect("Invalid message catalog");
+
+    let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+    // 菜单和标题不需要双向文本隔离字符
+    bundle.set_use_isolating(false);
+    bundle.add_resource(resource).expect("Duplicate message in catalog");
+
+    bundle
+}
+
+// 根据系统语言选择界面语言，不支持的语言使用英文
+pub fn system_locale() -> Locale {
+    match sys_locale::get_locale() {
+        Some(tag) if tag.to_ascii_lowercase().starts_with("zh") => Locale::ZhCn,
+        _ => Locale::En,
+    }
+}
+
+// 获取当前界面语言：设置中指定的语言优先，否则跟随系统
+pub fn current<R: Runtime>(app_handle: &AppHandle<R>) -> Locale {
+    match settings::get(app_handle).language {
+        Language::System => system_locale(),
+        Language::En => Locale::En,
+        Language::ZhCn => Locale::ZhCn,
+    }
+}
+
+// 启动时和界面语言变化后记录的语言，供无法访问 AppHandle 的错误信息使用
+static ACTIVE_LOCALE: RwLock<Option<Locale>> = RwLock::new(None);
+
+// 记录当前界面语言
+pub fn set_active<R: Runtime>(app_handle: &AppHandle<R>) {
+    *ACTIVE_LOCALE.write().unwrap_or_else(PoisonError::into_inner) = Some(current(app_handle));
+}
+
+// 获取记录的界面语言，尚未记录时跟随系统
+pub fn active() -> Locale {
+    ACTIVE_LOCALE.read().unwrap_or_else(PoisonError::into_inner).unwrap_or_else(system_locale)
+}
+
+// 翻译消息
+pub fn t<R: Runtime>(app_handle: &AppHandle<R>, id: &str) -> String {
+    format(current(app_handle), id, None)
+}
+
+// 翻译带参数的消息
+pub fn t_args<R: Runtime>(app_handle: &AppHandle<R>, id: &str, args: &[(&str, &str)]) -> String {
+    format(current(app_handle), id, Some(&fluent_args(args)))
+}
+
+// 使用记录的界面语言翻译带参数的消息
+pub fn tr(id: &str, args: &[(&str, &str)]) -> String {
+    format(active(), id, Some(&fluent_args(args)))
+}
+
+// 定义页面使用的翻译
+#[derive(Debug, Serialize)]
+pub struct Translations {
+    pub locale: &'static str,
+    pub messages: HashMap<String, String>,
+}
+
+// 获取以 `prefix` 开头的全部消息，消息中的参数保留为 `{$name}`，由页面替换
+pub fn translations<R: Runtime>(app_handle: &AppHandle<R>, prefix: &str) -> Translations {
+    let locale = current(app_handle);
+    // 英文目录包含全部消息
+    let messages = Locale::En
+        .source()
+        .lines()
+        .filter_map(|line| line.split_once(" = "))
+        .map(|(id, _)| id.trim())
+        .filter(|id| id.starts_with(prefix))
+        .map(|id| (id.to_string(), format(locale, id, None)))
+        .collect();
+
+    Translations {
+        locale: locale.tag(),
+        messages,
+    }
+}
+
+fn fluent_args<'a>(args: &[(&'a str, &'a str)]) -> FluentArgs<'a> {
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, *value);
+    }
+
+    fluent_args
+}
+
+fn format(locale: Locale, id: &str, args: Option<&FluentArgs>) -> String {
+    let bundles = bundles();
+    let bundle = match locale {
+        Locale::En => &bundles.en,
+        Locale::ZhCn => &bundles.zh_cn,
+    };
+
+    // 缺少翻译时回退到英文，仍然缺少时显示消息 ID
+    let pattern = bundle
+        .get_message(id)
+        .and_then(|message| message.value())
+        .map(|pattern| (bundle, pattern))
+        .or_else(|| {
+            let message = bundles.en.get_message(id)?;
+            Some((&bundles.en, message.value()?))
+        });
+    let Some((bundle, pattern)) = pattern else {
+        return id.to_string();
+    };
+
+    let mut errors = Vec::new();
+    bundle.format_pattern(pattern, args, &mut errors).into_owned()
+}
+
+// 界面语言变化后更新窗口标题（托盘菜单由设置模块重新创建）
+pub fn apply_titles<R: Runtime>(app_handle: &AppHandle<R>) -> Result<(), String> {
+    if let Some(window) = app_handle.get_webview_window("settings") {
+        window.set_title(&t(app_handle, "settings-window-title")).map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message_ids(locale: Locale) -> Vec<&'static str> {
+        let mut ids: Vec<&str> = locale
+            .source()
+            .lines()
+            .filter_map(|line| line.split_once(" = "))
+            .map(|(id, _)| id.trim())
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    #[test]
+    fn catalogs_define_the_same_messages() {
+        // 加载时会检查语法和重复的消息
+        bundles();
+
+        assert_eq!(message_ids(Locale::En), message_ids(Locale::ZhCn));
+    }
+
+    #[test]
+    fn messages_are_formatted_with_arguments() {
+        let args = fluent_args(&[("service", "Poe")]);
+
+        assert_eq!(format(Locale::En, "error-unsupported-service", Some(&args)), "Unsupported service: Poe");
+        assert_eq!(format(Locale::ZhCn, "error-unsupported-service", Some(&args)), "不支持的服务类型: Poe");
+        // 缺少参数时保留占位符，由设置页面替换
+        assert_eq!(format(Locale::En, "settings-mode-pinned", None), "{$service}: always on top");
+        assert_eq!(format(Locale::En, "missing-message", None), "missing-message");
+    }
+}
@@ -0,0 +1,298 @@
+use crate::cli::{Command, LaunchArgs};
+use crate::logging;
+use interprocess::local_socket::{prelude::*, Listener, ListenerOptions, Name, Stream};
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Write};
+use std::time::Duration;
+use tauri::AppHandle;
+
+// 本地套接字名称，每个用户一个实例
+pub const SOCKET_NAME: &str = "com.lif.ai.assistant";
+
+// 等待请求和响应的最长时间，避免对方不回应时一直阻塞
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(30);
+
+// 定义 IPC 请求，每个请求是一行 JSON
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    // 第二个实例启动时转发的命令行参数
+    Launch(LaunchArgs),
+    // 命令行客户端发送的子命令
+    Command(CommandRequest),
+}
+
+// 定义命令行客户端发送的子命令请求，设置了控制令牌时需要附带令牌
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommandRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    pub command: Command,
+}
+
+// 定义 IPC 响应
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+}
+
+impl Response {
+    // 创建成功响应
+    pub fn success(result: Option<serde_json::Value>) -> Self {
+        Self {
+            ok: true,
+            error: None,
+            result,
+        }
+    }
+
+    // 创建失败响应
+    pub fn error(error: String) -> Self {
+        Self {
+            ok: false,
+            error: Some(error),
+            result: None,
+        }
+    }
+}
+
+// 获取 Unix 上的套接字文件路径，`name` 区分不同用途的套接字
+#[cfg(unix)]
+pub fn socket_path(name: &str) -> io::Result<std::path::PathBuf> {
+    let user = std::env::var("USER").unwrap_or_default();
+    let dir = socket_dir(std::env::var_os("XDG_RUNTIME_DIR").map(std::path::PathBuf::from), &std::env::temp_dir())?;
+
+    Ok(dir.join(format!("{}-{}.sock", name, user)))
+}
+
+// 获取存放套接字文件的目录：优先使用 XDG_RUNTIME_DIR，未设置时在临时目录中创建只有当前用户能访问的目录。
+// 目录属于其他用户或其他用户可以访问时拒绝使用，避免连接到别人创建的套接字
+#[cfg(unix)]
+fn socket_dir(runtime_dir: Option<std::path::PathBuf>, temp_dir: &std::path::Path) -> io::Result<std::path::PathBuf> {
+    use std::os::unix::fs::DirBuilderExt;
+
+    let dir = match runtime_dir {
+        Some(dir) if dir.is_absolute() => dir,
+        _ => {
+            let dir = temp_dir.join(format!("{}-{}", SOCKET_NAME, current_uid()));
+            match std::fs::DirBuilder::new().mode(0o700).create(&dir) {
+                Err(e) if e.kind() != io::ErrorKind::AlreadyExists => return Err(e),
+                _ => dir,
+            }
+        }
+    };
+    check_private_dir(&dir, current_uid())?;
+
+    Ok(dir)
+}
+
+#[cfg(unix)]
+fn check_private_dir(dir: &std::path::Path, uid: u32) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = std::fs::symlink_metadata(dir)?;
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            crate::i18n::tr("error-socket-dir-insecure", &[("path", &dir.display().to_string())]),
+        ));
+    }
+
+    Ok(())
+}
+
+// 连接前检查套接字文件属于当前用户，避免把请求和令牌发给其他用户创建的套接字
+#[cfg(unix)]
+pub fn check_owner(path: &std::path::Path, uid: u32) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    if std::fs::symlink_metadata(path)?.uid() != uid {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            crate::i18n::tr("error-socket-owner", &[("path", &path.display().to_string())]),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+pub fn current_uid() -> u32 {
+    // SAFETY: getuid 没有参数，总是成功
+    unsafe { libc::getuid() }
+}
+
+// 获取本地套接字名称：Unix 上是运行时目录中的套接字文件，Windows 上是命名管道
+fn socket_name() -> io::Result<Name<'static>> {
+    #[cfg(unix)]
+    {
+        use interprocess::local_socket::GenericFilePath;
+
+        socket_path(SOCKET_NAME)?.to_fs_name::<GenericFilePath>()
+    }
+
+    #[cfg(windows)]
+    {
+        use interprocess::local_socket::GenericNamespaced;
+
+        let user = std::env::var("USERNAME").unwrap_or_default();
+        format!("{}-{}", SOCKET_NAME, user).to_ns_name::<GenericNamespaced>()
+    }
+}
+
+// 发送请求给正在运行的实例；没有实例在运行时返回 `Ok(None)`
+pub fn send(request: &Request) -> io::Result<Option<Response>> {
+    #[cfg(unix)]
+    match check_owner(&socket_path(SOCKET_NAME)?, current_uid()) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        result => result?,
+    }
+
+    let stream = match Stream::connect(socket_name()?) {
+        Ok(stream) => stream,
+        Err(_) => return Ok(None),
+    };
+    stream.set_recv_timeout(Some(RESPONSE_TIMEOUT))?;
+
+    let mut reader = BufReader::new(stream);
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    reader.get_mut().write_all(line.as_bytes())?;
+
+    let mut response = String::new();
+    reader.read_line(&mut response)?;
+
+    Ok(Some(serde_json::from_str(&response)?))
+}
+
+// 开始监听其他实例的请求；另一个实例已经在监听时返回 `AddrInUse` 错误
+pub fn listen(app_handle: AppHandle) -> io::Result<()> {
+    let listener = match create_listener() {
+        // 套接字文件存在但无法连接，说明是上次异常退出时残留的文件
+        #[cfg(unix)]
+        Err(e) if e.kind() == io::ErrorKind::AddrInUse && Stream::connect(socket_name()?).is_err() => {
+            std::fs::remove_file(socket_path(SOCKET_NAME)?)?;
+            create_listener()?
+        }
+        result => result?,
+    };
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming().filter_map(Result::ok) {
+            let app_handle = app_handle.clone();
+            std::thread::spawn(move || {
+                logging::log_err!(handle_connection(&app_handle, stream), "处理连接失败");
+            });
+        }
+    });
+
+    Ok(())
+}
+
+// 创建监听器，Unix 上的套接字文件只允许当前用户访问
+fn create_listener() -> io::Result<Listener> {
+    let options = ListenerOptions::new().name(socket_name()?);
+    #[cfg(unix)]
+    let options = {
+        use interprocess::os::unix::local_socket::ListenerOptionsExt;
+
+        options.mode(0o600)
+    };
+
+    options.create_sync()
+}
+
+fn handle_connection(app_handle: &AppHandle, stream: Stream) -> io::Result<()> {
+    stream.set_recv_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+
+    let response = match serde_json::from_str::<Request>(&line) {
+        Ok(request) => handle_request(app_handle, request),
+        Err(e) => Response::error(e.to_string()),
+    };
+
+    let mut line = serde_json::to_string(&response)?;
+    line.push('\n');
+    reader.get_mut().write_all(line.as_bytes())
+}
+
+fn handle_request(app_handle: &AppHandle, request: Request) -> Response {
+    match request {
+        Request::Launch(args) => {
+            let app_handle_clone = app_handle.clone();
+            let result = app_handle.run_on_main_thread(move || {
+                crate::handle_launch_args(&app_handle_clone, &args);
+            });
+
+            match result {
+                Ok(()) => Response::success(None),
+                Err(e) => Response::error(e.to_string()),
+            }
+        }
+        // 与本地控制接口使用同一个令牌，任何本地进程都能连接这个套接字
+        Request::Command(request) if !crate::control::authorize(app_handle, request.token.as_deref()) => {
+            Response::error(crate::i18n::tr("error-invalid-token", &[]))
+        }
+        Request::Command(request) => match crate::control::execute(app_handle, request.command) {
+            Ok(result) => Response::success(Some(result)),
+            Err(e) => Response::error(e),
+        },
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_dir;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn creates_a_private_directory_without_xdg_runtime_dir() {
+        let temp = temp_dir("socket");
+
+        let dir = socket_dir(None, &temp).unwrap();
+        assert_eq!(dir, temp.join(format!("{}-{}", SOCKET_NAME, current_uid())));
+        assert_eq!(std::fs::metadata(&dir).unwrap().permissions().mode() & 0o777, 0o700);
+        // 再次调用时使用已有的目录
+        assert_eq!(socket_dir(None, &temp).unwrap(), dir);
+    }
+
+    #[test]
+    fn uses_xdg_runtime_dir_when_it_is_private() {
+        let runtime = temp_dir("runtime");
+        std::fs::set_permissions(&runtime, std::fs::Permissions::from_mode(0o700)).unwrap();
+
+        assert_eq!(socket_dir(Some(runtime.clone()), &temp_dir("unused")).unwrap(), runtime);
+    }
+
+    #[test]
+    fn refuses_directories_other_users_can_access() {
+        let temp = temp_dir("shared");
+        let dir = temp.join(format!("{}-{}", SOCKET_NAME, current_uid()));
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(socket_dir(None, &temp).unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700)).unwrap();
+        assert!(check_private_dir(&dir, current_uid()).is_ok());
+        assert_eq!(check_private_dir(&dir, current_uid() + 1).unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn checks_the_socket_owner_before_connecting() {
+        let path = temp_dir("owner").join("app.sock");
+        assert_eq!(check_owner(&path, current_uid()).unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        std::fs::write(&path, "").unwrap();
+        assert!(check_owner(&path, current_uid()).is_ok());
+        assert_eq!(check_owner(&path, current_uid() + 1).unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+    }
+}
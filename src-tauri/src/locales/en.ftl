@@ -0,0 +1,169 @@
+# Tray menu
+tray-show = Show
+tray-hide = Hide
+tray-switch-to = Switch to { $service }
+tray-switch-to-open = Switch to { $service } (open)
+tray-pin = Always on top
+tray-compact = Compact mode
+tray-popover = Pop up by the tray icon
+tray-auto-hide = Hide when focus is lost
+tray-settings = Settings
+tray-quit = Quit
+tray-tooltip = AI Assistant - { $service }
+tray-tooltip-pending = AI Assistant - { $service } (new response)
+
+# Windows
+settings-window-title = AI Assistant Settings
+
+# Notifications
+notification-response-finished = { $service } finished responding
+notification-response-fallback = The response is ready
+notification-open = Open
+notification-tray-title = AI Assistant is still running
+notification-tray-body = Closed windows are hidden in the tray. Choose Quit from the tray menu to exit, or change this in Settings.
+
+# Errors
+error-unsupported-service = Unsupported service: { $service }
+error-history-not-found = History entry not found: { $id }
+error-foreign-url = { $url } is not a { $service } page
+error-open-service-window = Could not open the { $service } window
+error-browser-emulation-missing = Browser emulation script not found: { $path }
+error-not-found = File not found: { $path }
+error-io = Could not read or write { $path }: { $message }
+error-invalid-data = The contents of { $path } are invalid: { $message }
+error-webview = Could not run the script: { $message }
+error-token-whitespace = The access token must not contain whitespace
+error-invalid-settings = Invalid settings: { $error }
+error-invalid-log-filter = Invalid log level: { $error }
+error-invalid-token = Invalid token
+error-authentication-required = Call authenticate with the token first
+error-jsonrpc-version = jsonrpc must be "2.0"
+error-unknown-method = Unknown method: { $method }
+error-control-unsupported = The local control interface is only supported on Unix
+error-socket-dir-insecure = { $path } must be a directory only the current user can access
+error-socket-owner = The socket { $path } belongs to another user
+error-service-window-closed = The { $service } window is not open
+error-no-conversation = There is no conversation to export on the current page
+error-registry-write = Could not write to the registry
+error-registry-delete = Could not delete the registry entry
+
+# Command line
+cli-usage = Usage: app show <service> | app hide | app ask <service> <prompt> | app open <url> | app export <service> [--format md|json] | app status
+cli-not-running = AI Assistant is not running
+cli-unsupported-format = Unsupported export format: { $format }
+
+# Links
+link-invalid = Invalid link: { $error }
+link-unsupported-scheme = Unsupported link scheme: { $scheme }
+link-missing-param = Missing parameter: { $name }
+link-prompt-too-long = The prompt is too long (at most { $max } characters)
+link-invalid-conversation = Invalid conversation address: { $error }
+link-unknown-conversation = The conversation address does not belong to any service: { $url }
+link-unsupported-action = Unsupported link action: { $action }
+
+# Configuration bundles
+config-export-passphrase-required = A passphrase is required to export credentials
+config-invalid-bundle = Invalid configuration bundle: { $error }
+config-wrong-format = Invalid configuration bundle: wrong format
+config-newer-version = The bundle was exported by a newer version ({ $version }); please upgrade first
+config-unsupported-service = Service { $service } is not supported here; its settings will be ignored
+config-import-passphrase-required = The bundle contains credentials; enter the passphrase
+config-invalid-credentials = Invalid credentials: { $error }
+config-invalid-key-parameters = Invalid key parameters
+config-key-failed = Could not create the key
+config-random-failed = Could not generate random data
+config-encrypt-failed = Encryption failed
+config-unsupported-kdf = Unsupported key derivation algorithm: { $kdf }
+config-unsupported-iterations = Unsupported key iteration count: { $iterations }
+config-invalid-credential-data = Invalid credential data
+config-decrypt-failed = Wrong passphrase or corrupted credentials
+
+# Settings page
+settings-accounts = Accounts
+settings-service = Service
+settings-username = Username or email
+settings-username-placeholder = Enter your username or email
+settings-password = Password
+settings-password-placeholder = Enter your password
+settings-save-credentials = Save credentials
+settings-delete-credentials = Delete credentials
+settings-credentials-saved = Credentials saved!
+settings-saved-credentials = Saved credentials
+settings-load = Load
+settings-delete = Delete
+settings-credentials-required = Enter a username and password
+settings-save-credentials-failed = Could not save the credentials: { $error }
+settings-delete-credentials-confirm = Delete the credentials for { $service }?
+settings-delete-credentials-failed = Could not delete the credentials: { $error }
+settings-error-unknown-service = Unsupported service: { $service }
+settings-error-invalid-credentials = The credentials file is corrupted. Delete it and save again ({ $path })
+settings-startup = Startup
+settings-default-service = Service shown on first launch
+settings-theme = Theme
+settings-theme-system = Follow the system
+settings-theme-light = Light
+settings-theme-dark = Dark
+settings-language = Language / 界面语言
+settings-language-system = Follow the system
+settings-restore-last-url = Restore the last conversation on startup (otherwise always open the service home page)
+settings-launch-at-login = Launch at login (runs in the tray only)
+settings-start-hidden = Do not show a window on startup, run in the tray only
+settings-preload = Preload in the background after startup (opens without waiting for the page, but uses more memory)
+settings-windows = Windows
+settings-close-behavior = When the window close button is clicked
+settings-close-hide = Hide to the tray
+settings-close-close = Close the window (frees memory, the page reloads when reopened)
+settings-close-quit = Quit the app
+settings-mode-pinned = { $service }: always on top
+settings-mode-compact = { $service }: compact mode
+settings-mode-popover = { $service }: pop up by the tray icon (hides when focus is lost)
+settings-mode-auto-hide = { $service }: hide when focus is lost
+settings-compact-hide-sidebar = Hide the service sidebar in compact mode
+settings-tray = Tray icon
+settings-tray-left-click = Click
+settings-tray-double-click = Double-click
+settings-tray-middle-click = Middle-click
+settings-tray-right-click = Right-click (most systems always show the menu)
+settings-tray-toggle = Show or hide the windows
+settings-tray-show-mru = Show the most recently used service
+settings-tray-quick-ask = Quick ask
+settings-tray-hide = Hide all windows
+settings-tray-menu = Show the menu
+settings-tray-nothing = Do nothing
+settings-save-settings-failed = Could not save the settings: { $error }
+settings-control = Control interface
+settings-control-token = Access token (leave empty to let any program run by the same user control the app)
+settings-control-token-placeholder = Editor plugins and scripts must call authenticate with this token first
+settings-save-token = Save token
+settings-token-saved = Token saved
+settings-config = Import and export configuration
+settings-config-path = Configuration file path
+settings-config-path-placeholder = For example /home/me/ai-assistant-config.json
+settings-config-include-secrets = Include credentials and the access token when exporting (encrypted with the passphrase below)
+settings-config-passphrase = Passphrase
+settings-config-passphrase-placeholder = Required to export or import credentials
+settings-config-export = Export
+settings-config-preview = Preview import
+settings-config-import = Import
+settings-config-exported = Configuration exported
+settings-config-export-failed = Could not export the configuration: { $error }
+settings-config-invalid = Invalid configuration bundle: { $error }
+settings-config-imported = Configuration imported
+settings-config-import-failed = Could not import the configuration: { $error }
+settings-import-credential = Credentials: { $credential }
+settings-import-no-changes = Nothing to import
+settings-history = Conversation history
+settings-history-placeholder = Search archived conversations
+settings-history-search = Search
+settings-history-empty = No matching conversations
+settings-history-open-failed = Could not open the conversation: { $error }
+settings-logs = Logs
+settings-log-filter = Log level (can be set per module, for example info,app::rpc=debug)
+settings-log-filter-save = Save log level
+settings-log-refresh = Refresh logs
+settings-diagnostics = Diagnostics
+settings-diagnostics-path = Report path (optional)
+settings-diagnostics-path-placeholder = For example /home/me/ai-assistant-diagnostics.json
+settings-diagnostics-collect = Collect diagnostics
+settings-diagnostics-saved = Diagnostics report saved
+settings-diagnostics-failed = Could not collect diagnostics: { $error }
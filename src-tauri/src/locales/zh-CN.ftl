@@ -0,0 +1,169 @@
+# 托盘菜单
+tray-show = 显示
+tray-hide = 隐藏
+tray-switch-to = 切换到 { $service }
+tray-switch-to-open = 切换到 { $service }（已打开）
+tray-pin = 窗口置顶
+tray-compact = 紧凑模式
+tray-popover = 在托盘图标旁弹出
+tray-auto-hide = 失去焦点时隐藏
+tray-settings = 设置
+tray-quit = 退出
+tray-tooltip = AI Assistant - { $service }
+tray-tooltip-pending = AI Assistant - { $service }（有新回答）
+
+# 窗口
+settings-window-title = AI Assistant 设置
+
+# 通知
+notification-response-finished = { $service } 已完成回答
+notification-response-fallback = 回答已生成
+notification-open = 打开
+notification-tray-title = AI Assistant 仍在运行
+notification-tray-body = 关闭的窗口已隐藏到托盘。要退出应用，请在托盘菜单中选择“退出”，也可以在设置中修改关闭窗口的行为。
+
+# 错误
+error-unsupported-service = 不支持的服务类型: { $service }
+error-history-not-found = 找不到历史记录: { $id }
+error-foreign-url = { $url } 不是 { $service } 的页面
+error-open-service-window = 无法打开服务窗口: { $service }
+error-browser-emulation-missing = 找不到浏览器模拟脚本文件: { $path }
+error-not-found = 找不到文件: { $path }
+error-io = 读写文件 { $path } 失败: { $message }
+error-invalid-data = 文件 { $path } 的内容无效: { $message }
+error-webview = 执行脚本失败: { $message }
+error-token-whitespace = 访问令牌不能包含空白字符
+error-invalid-settings = 无效的设置: { $error }
+error-invalid-log-filter = 无效的日志级别设置: { $error }
+error-invalid-token = 令牌无效
+error-authentication-required = 需要先调用 authenticate 验证令牌
+error-jsonrpc-version = jsonrpc 必须为 "2.0"
+error-unknown-method = 未知的方法: { $method }
+error-control-unsupported = 本地控制接口仅支持 Unix 平台
+error-socket-dir-insecure = { $path } 必须是只有当前用户可以访问的目录
+error-socket-owner = 套接字 { $path } 属于其他用户
+error-service-window-closed = 服务窗口未打开: { $service }
+error-no-conversation = 当前页面没有可导出的对话
+error-registry-write = 无法写入注册表
+error-registry-delete = 无法删除注册表项
+
+# 命令行
+cli-usage = 用法: app show <service> | app hide | app ask <service> <prompt> | app open <url> | app export <service> [--format md|json] | app status
+cli-not-running = AI Assistant 未运行
+cli-unsupported-format = 不支持的导出格式: { $format }
+
+# 链接
+link-invalid = 无效的链接: { $error }
+link-unsupported-scheme = 不支持的链接协议: { $scheme }
+link-missing-param = 缺少参数: { $name }
+link-prompt-too-long = 提示词过长，最多 { $max } 个字符
+link-invalid-conversation = 无效的对话地址: { $error }
+link-unknown-conversation = 对话地址不属于任何服务: { $url }
+link-unsupported-action = 不支持的链接操作: { $action }
+
+# 配置包
+config-export-passphrase-required = 导出凭证需要设置密码
+config-invalid-bundle = 无效的配置包: { $error }
+config-wrong-format = 无效的配置包: 格式不正确
+config-newer-version = 配置包由更新版本的应用（{ $version }）导出，请先升级
+config-unsupported-service = 本机不支持服务 { $service }，相关设置将被忽略
+config-import-passphrase-required = 配置包包含凭证，需要输入密码
+config-invalid-credentials = 无效的凭证: { $error }
+config-invalid-key-parameters = 无效的密钥参数
+config-key-failed = 无法创建密钥
+config-random-failed = 无法生成随机数
+config-encrypt-failed = 加密失败
+config-unsupported-kdf = 不支持的密钥派生算法: { $kdf }
+config-unsupported-iterations = 不支持的密钥迭代次数: { $iterations }
+config-invalid-credential-data = 无效的凭证数据
+config-decrypt-failed = 密码错误或凭证已损坏
+
+# 设置页面
+settings-accounts = 账号管理
+settings-service = 服务
+settings-username = 用户名/邮箱
+settings-username-placeholder = 输入您的用户名或邮箱
+settings-password = 密码
+settings-password-placeholder = 输入您的密码
+settings-save-credentials = 保存凭证
+settings-delete-credentials = 删除凭证
+settings-credentials-saved = 凭证已保存！
+settings-saved-credentials = 已保存的凭证
+settings-load = 加载
+settings-delete = 删除
+settings-credentials-required = 请输入用户名和密码
+settings-save-credentials-failed = 保存凭证失败: { $error }
+settings-delete-credentials-confirm = 确定要删除 { $service } 的凭证吗？
+settings-delete-credentials-failed = 删除凭证失败: { $error }
+settings-error-unknown-service = 不支持的服务: { $service }
+settings-error-invalid-credentials = 凭证文件已损坏，请删除后重新保存（{ $path }）
+settings-startup = 启动
+settings-default-service = 首次启动时显示的服务
+settings-theme = 主题
+settings-theme-system = 跟随系统
+settings-theme-light = 浅色
+settings-theme-dark = 深色
+settings-language = 界面语言 / Language
+settings-language-system = 跟随系统
+settings-restore-last-url = 启动时恢复上次打开的对话（关闭后总是打开服务首页）
+settings-launch-at-login = 登录时自动启动（启动后只在托盘中运行）
+settings-start-hidden = 启动时不显示窗口，只在托盘中运行
+settings-preload = 启动后在后台预先加载（打开时无需等待页面加载，但会占用更多内存）
+settings-windows = 窗口
+settings-close-behavior = 点击窗口关闭按钮时
+settings-close-hide = 隐藏到托盘
+settings-close-close = 关闭窗口（释放内存，再次打开时重新加载页面）
+settings-close-quit = 退出应用
+settings-mode-pinned = { $service } 窗口置顶
+settings-mode-compact = { $service } 紧凑模式
+settings-mode-popover = { $service } 在托盘图标旁弹出（失去焦点时自动隐藏）
+settings-mode-auto-hide = { $service } 失去焦点时自动隐藏
+settings-compact-hide-sidebar = 紧凑模式下隐藏服务的侧边栏
+settings-tray = 托盘图标
+settings-tray-left-click = 单击
+settings-tray-double-click = 双击
+settings-tray-middle-click = 中键单击
+settings-tray-right-click = 右键单击（大多数系统总会显示菜单）
+settings-tray-toggle = 显示或隐藏窗口
+settings-tray-show-mru = 显示最近使用的服务
+settings-tray-quick-ask = 快速提问
+settings-tray-hide = 隐藏所有窗口
+settings-tray-menu = 显示菜单
+settings-tray-nothing = 无操作
+settings-save-settings-failed = 保存设置失败: { $error }
+settings-control = 控制接口
+settings-control-token = 访问令牌（留空则同一用户的任何程序都可以控制应用）
+settings-control-token-placeholder = 编辑器插件和脚本需先用此令牌调用 authenticate
+settings-save-token = 保存令牌
+settings-token-saved = 令牌已保存
+settings-config = 配置导入导出
+settings-config-path = 配置文件路径
+settings-config-path-placeholder = 例如 /home/me/ai-assistant-config.json
+settings-config-include-secrets = 导出时包含凭证和访问令牌（使用下面的密码加密）
+settings-config-passphrase = 密码
+settings-config-passphrase-placeholder = 导出或导入凭证时需要
+settings-config-export = 导出
+settings-config-preview = 预览导入
+settings-config-import = 确认导入
+settings-config-exported = 配置已导出
+settings-config-export-failed = 导出配置失败: { $error }
+settings-config-invalid = 配置包无效: { $error }
+settings-config-imported = 配置已导入
+settings-config-import-failed = 导入配置失败: { $error }
+settings-import-credential = 凭证: { $credential }
+settings-import-no-changes = 没有需要导入的变化
+settings-history = 对话历史
+settings-history-placeholder = 搜索已归档的对话
+settings-history-search = 搜索
+settings-history-empty = 没有找到匹配的对话
+settings-history-open-failed = 打开历史对话失败: { $error }
+settings-logs = 日志
+settings-log-filter = 日志级别（可按模块设置，例如 info,app::rpc=debug）
+settings-log-filter-save = 保存日志级别
+settings-log-refresh = 刷新日志
+settings-diagnostics = 诊断信息
+settings-diagnostics-path = 报告保存路径（可选）
+settings-diagnostics-path-placeholder = 例如 /home/me/ai-assistant-diagnostics.json
+settings-diagnostics-collect = 收集诊断信息
+settings-diagnostics-saved = 诊断报告已保存
+settings-diagnostics-failed = 收集诊断信息失败: { $error }
@@ -0,0 +1,96 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
+
+// 日志文件名前缀和后缀，例如 ai-assistant.2024-01-01.log
+const LOG_FILE_PREFIX: &str = "ai-assistant";
+const LOG_FILE_SUFFIX: &str = "log";
+
+// 最多保留的日志文件数（每天一个）
+const MAX_LOG_FILES: usize = 7;
+
+// 用于在运行时修改日志级别
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+// 日志目录
+static LOG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+// 记录被忽略的错误，事件的 target 是调用处所在的模块，便于按模块设置级别
+macro_rules! log_err {
+    ($result:expr, $($arg:tt)+) => {
+        if let Err(e) = $result {
+            tracing::warn!(error = %e, $($arg)+);
+        }
+    };
+}
+pub(crate) use log_err;
+
+// 初始化日志：按天滚动写入日志目录，同时输出到标准错误；`RUST_LOG` 优先于设置中的级别
+pub fn init(log_dir: &Path, directives: &str) -> Result<(), String> {
+    fs::create_dir_all(log_dir).map_err(|e| e.to_string())?;
+    let file = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(log_dir)
+        .map_err(|e| e.to_string())?;
+
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(directives))
+        .unwrap_or_else(|_| EnvFilter::new("info"));
+    let (filter, handle) = reload::Layer::new(filter);
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(file).with_ansi(false))
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .try_init()
+        .map_err(|e| e.to_string())?;
+
+    let _ = FILTER_HANDLE.set(handle);
+    let _ = LOG_DIR.set(log_dir.to_path_buf());
+
+    Ok(())
+}
+
+// 检查日志级别设置，例如 `info,app::rpc=debug`
+pub fn validate_filter(directives: &str) -> Result<(), String> {
+    EnvFilter::try_new(directives).map(|_| ()).map_err(|e| crate::i18n::tr("error-invalid-log-filter", &[("error", &e.to_string())]))
+}
+
+// 在运行时修改日志级别
+pub fn set_filter(directives: &str) -> Result<(), String> {
+    let Some(handle) = FILTER_HANDLE.get() else {
+        return Ok(());
+    };
+
+    let filter = EnvFilter::try_new(directives).map_err(|e| e.to_string())?;
+    handle.reload(filter).map_err(|e| e.to_string())
+}
+
+// 读取最新日志文件的最后几行
+pub fn recent_lines(limit: usize) -> Result<Vec<String>, String> {
+    let Some(log_dir) = LOG_DIR.get() else {
+        return Ok(Vec::new());
+    };
+
+    let latest = fs::read_dir(log_dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(LOG_FILE_PREFIX))
+        .max_by_key(|entry| entry.metadata().and_then(|m| m.modified()).ok());
+    let Some(latest) = latest else {
+        return Ok(Vec::new());
+    };
+
+    let contents = fs::read_to_string(latest.path()).map_err(|e| e.to_string())?;
+    let lines: Vec<&str> = contents.lines().collect();
+    let start = lines.len().saturating_sub(limit);
+
+    Ok(lines[start..].iter().map(|line| line.to_string()).collect())
+}
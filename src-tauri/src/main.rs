@@ -3,36 +3,49 @@
   windows_subsystem = "windows"
 )]
 
-use tauri::{Manager, WindowEvent};
-use tauri::menu::{Menu, MenuItem};
-use tauri::tray::TrayIconBuilder;
+use tauri::{Manager, Runtime, WindowEvent};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder};
+use tauri_plugin_deep_link::DeepLinkExt;
 use serde::{Serialize, Deserialize};
 use std::fs;
 use std::path::Path;
-use std::sync::Mutex;
 use std::time::Instant;
 use std::io::Read;
 
-// 定义应用状态结构体
-struct AppState {
-    // 跟踪窗口是否处于前台
-    gemini_focused: bool,
-    poe_focused: bool,
-    settings_focused: bool,
-    // 跟踪上次点击托盘图标的时间
-    last_tray_click_time: Instant,
-}
-
-impl Default for AppState {
-    fn default() -> Self {
-        Self {
-            gemini_focused: false,
-            poe_focused: false,
-            settings_focused: false,
-            last_tray_click_time: Instant::now(),
-        }
-    }
-}
+mod app_state;
+mod archive;
+mod auto_hide;
+mod autostart;
+mod cli;
+mod config_bundle;
+mod control;
+mod deep_link;
+mod diagnostics;
+mod error;
+mod i18n;
+mod ipc;
+mod logging;
+mod notifications;
+mod page_style;
+mod prompt;
+mod rpc;
+mod services;
+mod session;
+mod settings;
+mod shutdown;
+#[cfg(test)]
+mod test_support;
+mod theme;
+mod tray;
+mod tray_policy;
+mod window_modes;
+mod window_state;
+
+use app_state::{StateEvent, StateStore};
+use archive::{Archive, HistoryItem};
+use error::AppError;
+use settings::{CloseBehavior, Settings};
+use tray_policy::TrayAction;
 
 // 定义凭证结构体
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -42,8 +55,17 @@ struct Credentials {
     service: String,
 }
 
+// 获取服务的凭证文件路径，不支持的服务返回错误，避免拼接出任意路径
+fn credentials_path(service: &str) -> Result<String, AppError> {
+    services::find(service).ok_or_else(|| AppError::UnknownService { service: service.to_string() })?;
+
+    Ok(format!("credentials/{}.json", service))
+}
+
 // 保存凭证
-fn save_credentials_to_file(service: &str, username: &str, password: &str) -> Result<(), String> {
+fn save_credentials_to_file(service: &str, username: &str, password: &str) -> Result<(), AppError> {
+    let path = credentials_path(service)?;
+
     // 简单地将凭证保存到文件中
     let credentials = Credentials {
         username: username.to_string(),
@@ -51,20 +73,23 @@ fn save_credentials_to_file(service: &str, username: &str, password: &str) -> Re
         service: service.to_string(),
     };
 
-    let json = serde_json::to_string(&credentials).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string(&credentials).map_err(|e| AppError::InvalidData {
+        path: path.clone(),
+        message: e.to_string(),
+    })?;
 
     // 创建目录（如果不存在）
-    fs::create_dir_all("credentials").map_err(|e| e.to_string())?;
+    fs::create_dir_all("credentials").map_err(|e| AppError::io("credentials", e))?;
 
     // 保存到文件
-    fs::write(format!("credentials/{}.json", service), json).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| AppError::io(&path, e))?;
 
     Ok(())
 }
 
 // 获取凭证
-fn get_credentials_from_file(service: &str) -> Result<Option<Credentials>, String> {
-    let path = format!("credentials/{}.json", service);
+fn get_credentials_from_file(service: &str) -> Result<Option<Credentials>, AppError> {
+    let path = credentials_path(service)?;
 
     // 检查文件是否存在
     if !Path::new(&path).exists() {
@@ -72,22 +97,25 @@ fn get_credentials_from_file(service: &str) -> Result<Option<Credentials>, Strin
     }
 
     // 读取文件
-    let json = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let json = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
 
     // 解析 JSON
-    let credentials: Credentials = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+    let credentials: Credentials = serde_json::from_str(&json).map_err(|e| AppError::InvalidData {
+        path: path.clone(),
+        message: e.to_string(),
+    })?;
 
     Ok(Some(credentials))
 }
 
 // 删除凭证
-fn delete_credentials_from_file(service: &str) -> Result<(), String> {
-    let path = format!("credentials/{}.json", service);
+fn delete_credentials_from_file(service: &str) -> Result<(), AppError> {
+    let path = credentials_path(service)?;
 
     // 检查文件是否存在
     if Path::new(&path).exists() {
         // 删除文件
-        fs::remove_file(path).map_err(|e| e.to_string())?;
+        fs::remove_file(&path).map_err(|e| AppError::io(&path, e))?;
     }
 
     Ok(())
@@ -152,24 +180,24 @@ fn generate_login_script(service: &str, username: &str, password: &str) -> Strin
 
 // 定义命令：保存凭证
 #[tauri::command]
-fn save_credentials(service: String, username: String, password: String) -> Result<(), String> {
+fn save_credentials(service: String, username: String, password: String) -> Result<(), AppError> {
     save_credentials_to_file(&service, &username, &password)
 }
 
 // 定义命令：获取凭证
 #[tauri::command]
-fn get_credentials(service: String) -> Result<Option<Credentials>, String> {
+fn get_credentials(service: String) -> Result<Option<Credentials>, AppError> {
     get_credentials_from_file(&service)
 }
 
 // 定义命令：删除凭证
 #[tauri::command]
-fn delete_credentials(service: String) -> Result<(), String> {
+fn delete_credentials(service: String) -> Result<(), AppError> {
     delete_credentials_from_file(&service)
 }
 
 // 加载浏览器模拟脚本
-fn load_browser_emulation_script() -> Result<String, String> {
+fn load_browser_emulation_script() -> Result<String, AppError> {
     // 尝试从当前目录加载
     let mut path = "browser_emulation.js".to_string();
 
@@ -180,111 +208,565 @@ fn load_browser_emulation_script() -> Result<String, String> {
 
     // 如果 src-tauri 目录也不存在，返回错误
     if !Path::new(&path).exists() {
-        return Err(format!("找不到浏览器模拟脚本文件: {}", path));
+        return Err(AppError::BrowserEmulationMissing { path });
     }
 
-    let mut file = fs::File::open(&path).map_err(|e| e.to_string())?;
+    let mut file = fs::File::open(&path).map_err(|e| AppError::io(&path, e))?;
     let mut contents = String::new();
-    file.read_to_string(&mut contents).map_err(|e| e.to_string())?;
+    file.read_to_string(&mut contents).map_err(|e| AppError::io(&path, e))?;
     Ok(contents)
 }
 
 // 定义命令：自动登录
 #[tauri::command]
-fn auto_login(window: tauri::WebviewWindow, service: String) -> Result<bool, String> {
+fn auto_login<R: Runtime>(window: tauri::WebviewWindow<R>, service: String) -> Result<bool, AppError> {
     // 获取凭证
-    if let Ok(Some(creds)) = get_credentials_from_file(&service) {
-        // 生成登录脚本
-        let script = generate_login_script(&service, &creds.username, &creds.password);
+    let Some(creds) = get_credentials_from_file(&service)? else {
+        // 没有保存的凭证
+        return Ok(false);
+    };
 
-        // 执行登录脚本
-        if let Err(e) = window.eval(&script) {
-            return Err(e.to_string());
-        }
+    // 生成登录脚本
+    let script = generate_login_script(&service, &creds.username, &creds.password);
 
-        Ok(true)
-    } else {
-        // 没有保存的凭证
-        Ok(false)
-    }
+    // 执行登录脚本
+    window.eval(&script).map_err(AppError::webview)?;
+
+    Ok(true)
 }
 
 // 注入浏览器模拟脚本
 #[tauri::command]
-fn inject_browser_emulation(window: tauri::WebviewWindow) -> Result<bool, String> {
+fn inject_browser_emulation<R: Runtime>(window: tauri::WebviewWindow<R>) -> Result<bool, AppError> {
     // 加载浏览器模拟脚本
     let script = load_browser_emulation_script()?;
 
     // 执行脚本
-    if let Err(e) = window.eval(&script) {
-        return Err(e.to_string());
-    }
+    window.eval(&script).map_err(AppError::webview)?;
 
     Ok(true)
 }
 
+// 注入对话提取脚本，用于自动归档对话
+fn inject_conversation_extractor<R: Runtime>(window: &tauri::WebviewWindow<R>) -> Result<(), String> {
+    let script = include_str!("conversation_extractor.js").replace("__SERVICE__", window.label());
+
+    window.eval(&script).map_err(|e| e.to_string())
+}
+
+// 定义命令：归档对话（由对话提取脚本调用）
+#[tauri::command]
+fn archive_conversation(
+    window: tauri::WebviewWindow,
+    archive: tauri::State<'_, Archive>,
+    url: String,
+    title: String,
+    content: String,
+) -> Result<(), String> {
+    // 服务类型以调用窗口为准，不信任页面传入的数据，URL 也必须属于该服务的站点
+    let service = window.label();
+    archive::service_url(service, &url)?;
+
+    archive.record(service, &url, &title, &content)
+}
+
+// 定义命令：搜索历史对话
+#[tauri::command]
+fn search_history(archive: tauri::State<'_, Archive>, query: String) -> Result<Vec<HistoryItem>, String> {
+    archive.search(&query, 50)
+}
+
+// 定义命令：打开历史对话
+#[tauri::command]
+async fn open_history_item(app_handle: tauri::AppHandle, archive: tauri::State<'_, Archive>, id: i64) -> Result<(), String> {
+    let (item, url) = archive.resolve(id)?;
+
+    let window = show_service_window(&app_handle, &item.service)
+        .ok_or_else(|| i18n::t_args(&app_handle, "error-open-service-window", &[("service", &item.service)]))?;
+
+    window.navigate(url).map_err(|e| e.to_string())
+}
+
+// 定义命令：获取设置
+#[tauri::command]
+fn get_settings(app_handle: tauri::AppHandle) -> Settings {
+    settings::get(&app_handle)
+}
+
+// 定义命令：获取设置页面的翻译
+#[tauri::command]
+fn get_translations(app_handle: tauri::AppHandle) -> i18n::Translations {
+    i18n::translations(&app_handle, "settings-")
+}
+
+// 定义命令：修改设置，只需传入要修改的字段
+#[tauri::command]
+fn update_settings(app_handle: tauri::AppHandle, patch: serde_json::Value) -> Result<Settings, String> {
+    settings::patch(&app_handle, patch)
+}
+
+// 定义命令：导出配置，包含凭证时使用密码加密
+#[tauri::command]
+async fn export_config(
+    app_handle: tauri::AppHandle,
+    path: String,
+    include_secrets: bool,
+    passphrase: Option<String>,
+) -> Result<(), String> {
+    config_bundle::export(&app_handle, Path::new(&path), include_secrets, passphrase.as_deref())
+}
+
+// 定义命令：导入配置；未传入 apply 时只验证配置包并返回将要发生的变化
+#[tauri::command]
+async fn import_config(
+    app_handle: tauri::AppHandle,
+    path: String,
+    passphrase: Option<String>,
+    apply: Option<bool>,
+) -> Result<config_bundle::ImportPreview, String> {
+    config_bundle::import(&app_handle, Path::new(&path), passphrase.as_deref(), apply.unwrap_or(false))
+}
+
+// 定义命令：获取最近的日志
+#[tauri::command]
+fn get_recent_logs(lines: Option<usize>) -> Result<Vec<String>, String> {
+    logging::recent_lines(lines.unwrap_or(200))
+}
+
+// 定义命令：收集诊断信息，传入路径时同时保存为 JSON 文件
+#[tauri::command]
+async fn collect_diagnostics(
+    app_handle: tauri::AppHandle,
+    path: Option<String>,
+    log_lines: Option<usize>,
+) -> Result<diagnostics::Report, String> {
+    let report = diagnostics::collect(&app_handle, log_lines.unwrap_or(200))?;
+    if let Some(path) = path.filter(|p| !p.is_empty()) {
+        diagnostics::save(&report, Path::new(&path))?;
+    }
+
+    Ok(report)
+}
+
+// 记录服务窗口被使用，将其移到最近使用顺序的最前面
+fn touch_service<R: Runtime>(app_handle: &tauri::AppHandle<R>, service: &str) {
+  // 窗口获得焦点即视为已查看回答
+  app_handle.state::<StateStore>().dispatch(StateEvent::ServiceActivated { service: service.to_string() });
+
+  logging::log_err!(session::set_last_service(app_handle, service), "记录上次使用的服务失败");
+
+  // 更新托盘菜单、图标和提示
+  logging::log_err!(tray::refresh(app_handle), "更新托盘失败");
+
+  rpc::publish(app_handle, "service_activated", serde_json::json!({ "service": service }));
+}
+
+// 获取最近使用的服务；首次启动时使用设置中的默认服务
+fn last_active_service<R: Runtime>(app_handle: &tauri::AppHandle<R>) -> String {
+  if let Some(service) = app_handle.state::<StateStore>().snapshot().service_mru.first() {
+    return service.clone();
+  }
+
+  session::last_service(app_handle).unwrap_or_else(|| settings::get(app_handle).default_service)
+}
+
+// 获取有未查看回答的服务
+fn pending_responses<R: Runtime>(app_handle: &tauri::AppHandle<R>) -> Vec<String> {
+  app_handle.state::<StateStore>().snapshot().pending_responses
+}
+
+// 注入生成监视脚本，用于检测回答生成的开始和结束
+fn inject_generation_watcher<R: Runtime>(window: &tauri::WebviewWindow<R>) -> Result<(), String> {
+    let script = include_str!("generation_watcher.js").replace("__SERVICE__", window.label());
+
+    window.eval(&script).map_err(|e| e.to_string())
+}
+
+// 定义命令：暂停窗口的自动隐藏，直到窗口重新获得焦点（由焦点保护脚本调用）
+#[tauri::command]
+fn suspend_auto_hide(window: tauri::WebviewWindow, state: tauri::State<'_, StateStore>) {
+    state.dispatch(StateEvent::AutoHideSuspended { label: window.label().to_string() });
+}
+
+// 定义命令：服务开始生成回答（由生成监视脚本调用）
+#[tauri::command]
+fn generation_started(window: tauri::WebviewWindow, state: tauri::State<'_, StateStore>) -> Result<(), String> {
+    let service = window.label();
+    services::find(service)
+        .ok_or_else(|| i18n::t_args(window.app_handle(), "error-unsupported-service", &[("service", service)]))?;
+
+    state.dispatch(StateEvent::GenerationStarted { service: service.to_string() });
+
+    rpc::publish(window.app_handle(), "generation_started", serde_json::json!({ "service": service }));
+
+    Ok(())
+}
+
+// 定义命令：服务完成生成回答（由生成监视脚本调用）
+#[tauri::command]
+fn generation_finished(
+    window: tauri::WebviewWindow,
+    state: tauri::State<'_, StateStore>,
+    response: String,
+) -> Result<(), String> {
+    let service = window.label();
+    services::find(service)
+        .ok_or_else(|| i18n::t_args(window.app_handle(), "error-unsupported-service", &[("service", service)]))?;
+
+    // 窗口可见时用户能直接看到回答，无需通知
+    let hidden = !window.is_visible().unwrap_or(false) || window.is_minimized().unwrap_or(false);
+
+    state.dispatch(StateEvent::GenerationFinished {
+        service: service.to_string(),
+        response: response.clone(),
+        hidden,
+    });
+
+    rpc::publish(
+        window.app_handle(),
+        "generation_finished",
+        serde_json::json!({ "service": service, "response": response }),
+    );
+
+    if hidden {
+        // 托盘显示未查看标记，直到窗口获得焦点
+        logging::log_err!(tray::refresh(window.app_handle()), "更新托盘失败");
+        notifications::notify_response(window.app_handle(), service, &response)?;
+    }
+
+    Ok(())
+}
+
+// 按服务定义创建服务窗口，窗口创建后保持隐藏，由调用方决定是否显示
+fn create_service_window<R: Runtime>(app_handle: &tauri::AppHandle<R>, service: &services::ServiceDef) -> Option<tauri::WebviewWindow<R>> {
+  // 恢复上次打开的页面，否则打开首页
+  let url = session::restore_url(app_handle, service.id)
+    .unwrap_or_else(|| tauri::Url::parse(service.home_url).expect("Invalid URL"));
+  let result = tauri::WebviewWindow::builder(app_handle, service.id, tauri::WebviewUrl::External(url))
+    .title(service.name)
+    .resizable(true)
+    .fullscreen(false)
+    .inner_size(1440.0, 1080.0)
+    .center()
+    .visible(false)
+    .accept_first_mouse(true)
+    .user_agent(services::USER_AGENT)
+    .additional_browser_args(services::BROWSER_ARGS)
+    .build();
+  let window = match result {
+    Ok(window) => window,
+    Err(e) => {
+      tracing::error!(service = service.id, error = %e, "创建服务窗口失败");
+      return None;
+    }
+  };
+
+  // 恢复窗口位置、大小和窗口模式
+  window_state::restore(&window);
+  logging::log_err!(window_modes::apply(&window), "应用窗口模式失败");
+  logging::log_err!(theme::apply(&window), "应用主题失败");
+
+  // 添加焦点事件监听器，用于自动登录和跟踪焦点
+  let window_clone = window.clone();
+  let app_handle_clone = app_handle.clone();
+  let service_id = service.id;
+
+  window.on_window_event(move |event| {
+    if let WindowEvent::Focused(focused) = event {
+      // 更新窗口焦点状态
+      app_handle_clone.state::<StateStore>().dispatch(StateEvent::WindowFocused {
+        label: service_id.to_string(),
+        focused: *focused,
+      });
+      if *focused {
+        touch_service(&app_handle_clone, service_id);
+      }
+
+      // 如果窗口获得焦点，尝试自动登录
+      if *focused {
+        let window_clone2 = window_clone.clone();
+        std::thread::spawn(move || {
+          // 等待一段时间，确保页面完全加载
+          std::thread::sleep(std::time::Duration::from_secs(2));
+
+          // 注入浏览器模拟脚本
+          logging::log_err!(inject_browser_emulation(window_clone2.clone()), "注入浏览器模拟脚本失败");
+
+          // 注入对话提取脚本、生成监视脚本和焦点保护脚本
+          logging::log_err!(inject_conversation_extractor(&window_clone2), "注入对话提取脚本失败");
+          logging::log_err!(inject_generation_watcher(&window_clone2), "注入生成监视脚本失败");
+          logging::log_err!(auto_hide::inject_guard(&window_clone2), "注入焦点保护脚本失败");
+
+          // 尝试自动登录
+          logging::log_err!(auto_login(window_clone2, service_id.to_string()), "自动登录失败");
+        });
+      } else {
+        // 失去焦点时记录当前页面
+        logging::log_err!(session::record_window_url(&window_clone), "记录窗口 URL 失败");
+      }
+    }
+  });
+
+  Some(window)
+}
+
+// 切换到指定服务的窗口，并隐藏其他服务窗口；窗口不存在时创建
+fn show_service_window<R: Runtime>(app_handle: &tauri::AppHandle<R>, service: &str) -> Option<tauri::WebviewWindow<R>> {
+  let def = services::find(service)?;
+
+  for other in services::SERVICES.iter().filter(|s| s.id != service) {
+    if let Some(window) = app_handle.get_webview_window(other.id) {
+      logging::log_err!(window_state::hide(&window), "隐藏窗口失败");
+    }
+  }
+
+  let window = match app_handle.get_webview_window(service) {
+    Some(window) => window,
+    None => create_service_window(app_handle, def)?,
+  };
+
+  // 弹出模式的窗口显示在托盘图标旁
+  if window_modes::get(app_handle, service).popover {
+    logging::log_err!(window_modes::anchor_to_tray(&window), "移动弹出窗口失败");
+  }
+
+  logging::log_err!(window.show(), "显示窗口失败");
+  logging::log_err!(window.set_focus(), "聚焦窗口失败");
+  logging::log_err!(tray::refresh(app_handle), "更新托盘失败");
+
+  Some(window)
+}
+
+// 在后台创建设置中要预加载的服务窗口，打开时无需等待页面加载
+fn preload_service_windows<R: Runtime>(app_handle: &tauri::AppHandle<R>) {
+  for service in settings::get(app_handle).preload_services {
+    let Some(def) = services::find(&service) else {
+      continue;
+    };
+    if app_handle.get_webview_window(def.id).is_none() {
+      create_service_window(app_handle, def);
+    }
+  }
+}
+
+// 处理启动参数：显示服务窗口、打开 URL 并填入提示词
+fn handle_launch_args<R: Runtime>(app_handle: &tauri::AppHandle<R>, args: &cli::LaunchArgs) {
+  // 只带 --hidden 启动时不显示窗口
+  if args.hidden && args.service.is_none() && args.url.is_none() && args.prompt.is_none() {
+    return;
+  }
+
+  let url = args.url.as_deref().and_then(|url| tauri::Url::parse(url).ok());
+
+  // 不属于任何服务的 URL 使用系统浏览器打开
+  let owner = url.as_ref().and_then(|url| services::SERVICES.iter().find(|s| s.owns_url(url)));
+  if let (Some(url), None) = (&url, owner) {
+    logging::log_err!(webbrowser::open(url.as_str()), "使用系统浏览器打开链接失败");
+  }
+
+  // 优先使用 URL 所属的服务，其次是参数指定的服务，最后是最近使用的服务
+  let service = owner
+    .map(|s| s.id.to_string())
+    .or_else(|| args.service.clone().filter(|s| services::find(s).is_some()))
+    .unwrap_or_else(|| last_active_service(app_handle));
+
+  let Some(window) = show_service_window(app_handle, &service) else {
+    return;
+  };
+
+  let navigated = match url {
+    Some(url) if owner.is_some() => window.navigate(url).is_ok(),
+    _ => false,
+  };
+
+  if let Some(prompt) = args.prompt.clone() {
+    std::thread::spawn(move || {
+      // 打开新页面后等待一段时间，确保页面开始加载
+      if navigated {
+        std::thread::sleep(std::time::Duration::from_secs(2));
+      }
+
+      logging::log_err!(prompt::fill(&window, &prompt, false), "填入提示词失败");
+    });
+  }
+}
+
 fn main() {
+  let args: Vec<String> = std::env::args().skip(1).collect();
+
+  // 带子命令运行时作为命令行客户端，把命令发送给正在运行的实例并输出 JSON 结果
+  if let Some(command) = cli::Command::parse(&args) {
+    std::process::exit(cli::run(command));
+  }
+
+  // 解析命令行参数；通过 aiassistant:// 链接启动时由链接决定要执行的操作
+  let launch_args = match args.iter().find(|arg| deep_link::is_deep_link(arg)) {
+    Some(link) => deep_link::parse(link).unwrap_or_else(|e| {
+      eprintln!("忽略无效的链接 {}: {}", link, e);
+      cli::LaunchArgs::default()
+    }),
+    None => cli::LaunchArgs::parse(args),
+  };
+
+  // 已有实例在运行时，将参数转发给它后退出
+  if let Ok(Some(_)) = ipc::send(&ipc::Request::Launch(launch_args.clone())) {
+    return;
+  }
+
   tauri::Builder::default()
     .plugin(tauri_plugin_http::init())
-    .manage(Mutex::new(AppState::default()))
+    .plugin(tauri_plugin_store::Builder::default().build())
+    .plugin(tauri_plugin_deep_link::init())
+    .manage(StateStore::default())
+    .manage(settings::SettingsLock::default())
+    .manage(rpc::Subscribers::default())
+    .manage(autostart::system())
     .invoke_handler(tauri::generate_handler![
       save_credentials,
       get_credentials,
       delete_credentials,
       auto_login,
-      inject_browser_emulation
+      inject_browser_emulation,
+      archive_conversation,
+      search_history,
+      open_history_item,
+      get_settings,
+      update_settings,
+      get_translations,
+      export_config,
+      import_config,
+      get_recent_logs,
+      collect_diagnostics,
+      suspend_auto_hide,
+      generation_started,
+      generation_finished,
+      control::list_services,
+      control::get_status,
+      control::show_service,
+      control::hide_windows,
+      control::send_prompt,
+      control::get_last_response,
+      control::open_url,
+      control::export_conversation
     ])
-    .setup(|app| {
-      // 创建菜单项
-      let quit_item = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
-      let show_item = MenuItem::with_id(app, "show", "显示", true, None::<&str>)?;
-      let hide_item = MenuItem::with_id(app, "hide", "隐藏", true, None::<&str>)?;
-      let gemini_item = MenuItem::with_id(app, "gemini", "切换到 Gemini", true, None::<&str>)?;
-      let poe_item = MenuItem::with_id(app, "poe", "切换到 Poe", true, None::<&str>)?;
-      let settings_item = MenuItem::with_id(app, "settings", "设置", true, None::<&str>)?;
+    .on_page_load(|webview, payload| {
+      // 页面加载完成后记录服务窗口的 URL
+      if payload.event() == tauri::webview::PageLoadEvent::Finished {
+        logging::log_err!(session::record_url(webview.app_handle(), webview.label(), payload.url()), "记录页面 URL 失败");
+
+        // 页面重新加载后恢复紧凑模式和深色模式的样式，并重新注入对话提取脚本、生成监视脚本和焦点保护脚本
+        if let Some(window) = webview.app_handle().get_webview_window(webview.label()) {
+          logging::log_err!(window_modes::apply_page_style(&window), "恢复紧凑模式样式失败");
+          logging::log_err!(theme::apply_page_style(&window), "应用深色样式失败");
+          if services::find(window.label()).is_some() {
+            logging::log_err!(inject_conversation_extractor(&window), "注入对话提取脚本失败");
+            logging::log_err!(inject_generation_watcher(&window), "注入生成监视脚本失败");
+            logging::log_err!(auto_hide::inject_guard(&window), "注入焦点保护脚本失败");
+          }
+        }
+      }
+    })
+    .setup(move |app| {
+      // 迁移旧版本的设置文件
+      settings::migrate(app.app_handle())?;
+      i18n::set_active(app.app_handle());
+
+      // 初始化日志，写入应用日志目录
+      // 日志无法写入文件时不影响启动
+      if let Err(e) = logging::init(&app.path().app_log_dir()?, &settings::get(app.app_handle()).log_filter) {
+        eprintln!("初始化日志失败: {}", e);
+      }
+      tracing::info!(version = %app.package_info().version, "AI Assistant 启动");
+
+      // 应用更新后可执行文件的路径可能变化，启动时重新写入开机启动项
+      let autostart = app.state::<Box<dyn autostart::Autostart>>();
+      let launch_at_login = settings::get(app.app_handle()).launch_at_login;
+      if launch_at_login || autostart.is_enabled() {
+        logging::log_err!(autostart::set_enabled(autostart.as_ref(), launch_at_login), "更新开机启动项失败");
+      }
+
+      // 开机启动或设置了启动时隐藏时，只在托盘中运行
+      let start_hidden = launch_args.hidden || settings::get(app.app_handle()).start_hidden;
+
+      // 监听后续启动的实例转发的参数
+      match ipc::listen(app.app_handle().clone()) {
+        // 另一个实例同时启动并先开始了监听，将参数转发给它后退出
+        Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => {
+          if let Ok(Some(_)) = ipc::send(&ipc::Request::Launch(launch_args.clone())) {
+            tracing::info!("已有实例在运行，参数已转发");
+            std::process::exit(0);
+          }
+          return Err(e.into());
+        }
+        result => result?,
+      }
+
+      // 启动供编辑器插件和自动化脚本使用的本地控制接口，失败时不影响应用运行
+      if let Err(e) = rpc::listen(app.app_handle().clone()) {
+        tracing::warn!(error = %e, "无法启动本地控制接口");
+      }
+
+      // 打开本地对话归档
+      let data_dir = app.path().app_data_dir()?;
+      fs::create_dir_all(&data_dir)?;
+      app.manage(Archive::open(&data_dir.join("history.sqlite3"))?);
 
       // 创建菜单
-      let menu = Menu::with_items(app, &[&show_item, &hide_item, &gemini_item, &poe_item, &settings_item, &quit_item])?;
+      let menu = tray::build_menu(app.app_handle(), &last_active_service(app.app_handle()))?;
 
       // 创建系统托盘
-      let _tray = TrayIconBuilder::new()
+      let _tray = TrayIconBuilder::with_id(tray::TRAY_ID)
         .icon(app.default_window_icon().unwrap().clone()) // 使用应用默认图标
         .menu(&menu)
         .tooltip("AI Assistant")
         .build(app)?;
+      logging::log_err!(tray::refresh(app.app_handle()), "更新托盘失败");
+
+      // 重新打开上次退出时可见的服务窗口，其他窗口在使用时才创建
+      if !start_hidden {
+        for service in services::SERVICES {
+          if window_state::load(app.app_handle(), service.id).is_some_and(|state| state.visible) {
+            if let Some(window) = create_service_window(app.app_handle(), service) {
+              logging::log_err!(window.show(), "显示窗口失败");
+            }
+          }
+        }
+      }
 
-      // 为 Gemini 窗口添加事件处理
-      if let Some(window) = app.get_webview_window("gemini") {
-        let window_clone = window.clone();
-        let app_handle = app.app_handle().clone();
-
-        window.on_window_event(move |event| {
-          match event {
-            WindowEvent::Focused(focused) => {
-              // 更新窗口焦点状态
-              if let Ok(mut state) = app_handle.state::<Mutex<AppState>>().try_lock() {
-                state.gemini_focused = *focused;
-              }
+      // 首次启动时显示设置中的默认服务
+      if !start_hidden && session::last_service(app.app_handle()).is_none() {
+        show_service_window(app.app_handle(), &settings::get(app.app_handle()).default_service);
+      }
 
-              // 如果窗口获得焦点，尝试自动登录
-              if *focused {
-                let window_clone2 = window_clone.clone();
-                std::thread::spawn(move || {
-                  // 等待一段时间，确保页面完全加载
-                  std::thread::sleep(std::time::Duration::from_secs(2));
+      // 启动完成后在后台预加载服务窗口
+      let app_handle_clone = app.app_handle().clone();
+      std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_secs(3));
+        let app_handle = app_handle_clone.clone();
+        let result = app_handle_clone.run_on_main_thread(move || preload_service_windows(&app_handle));
+        logging::log_err!(result, "预加载服务窗口失败");
+      });
 
-                  // 注入浏览器模拟脚本
-                  let _ = inject_browser_emulation(window_clone2.clone());
+      // 处理本次启动的命令行参数
+      if launch_args.service.is_some() || launch_args.url.is_some() || launch_args.prompt.is_some() {
+        handle_launch_args(app.app_handle(), &launch_args);
+      }
 
-                  // 尝试自动登录
-                  let _ = auto_login(window_clone2, "gemini".to_string());
-                });
-              }
-            },
-            _ => {}
+      // Linux 和 Windows 上在运行时注册链接协议，未经安装包安装时也能打开链接；
+      // 这两个平台的链接作为命令行参数传入，由单实例转发处理
+      #[cfg(any(target_os = "linux", windows))]
+      logging::log_err!(app.deep_link().register_all(), "注册链接协议失败");
+
+      // macOS 上链接通过系统事件发送给正在运行的实例
+      let app_handle_clone = app.app_handle().clone();
+      app.deep_link().on_open_url(move |event| {
+        for link in event.urls() {
+          match deep_link::parse(link.as_str()) {
+            Ok(args) => handle_launch_args(&app_handle_clone, &args),
+            Err(e) => tracing::warn!(error = %e, %link, "忽略无效的链接"),
           }
-        });
-      }
+        }
+      });
 
       // 设置菜单事件处理程序
       let app_handle_clone = app.app_handle().clone();
@@ -294,120 +776,70 @@ fn main() {
 
         match id.as_str() {
             "quit" => {
-              app_handle.exit(0);
+              shutdown::quit(&app_handle);
             }
             "show" => {
-              // 显示 Gemini 窗口
-              if let Some(window) = app_handle.get_webview_window("gemini") {
-                let _ = window.show();
-                let _ = window.set_focus();
-              }
+              // 显示最近使用的服务窗口
+              show_service_window(&app_handle, &last_active_service(&app_handle));
             }
             "hide" => {
               // 隐藏所有窗口
-              if let Some(window) = app_handle.get_webview_window("gemini") {
-                let _ = window.hide();
-              }
-              if let Some(window) = app_handle.get_webview_window("poe") {
-                let _ = window.hide();
-              }
+              control::hide_windows(app_handle.clone());
             }
-            "gemini" => {
-              // 切换到 Gemini
-              if let Some(window) = app_handle.get_webview_window("poe") {
-                let _ = window.hide();
-              }
-              if let Some(window) = app_handle.get_webview_window("gemini") {
-                let _ = window.show();
-                let _ = window.set_focus();
-              }
+            id if services::find(id).is_some() => {
+              // 切换到服务窗口
+              show_service_window(&app_handle, id);
             }
-            "poe" => {
-              // 切换到 Poe
-              if let Some(window) = app_handle.get_webview_window("gemini") {
-                let _ = window.hide();
-              }
-
-              // 检查 Poe 窗口是否存在
-              if let Some(window) = app_handle.get_webview_window("poe") {
-                let _ = window.show();
-                let _ = window.set_focus();
-              } else {
-                // 如果 Poe 窗口不存在，则创建它
-                let user_agent = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/125.0.0.0 Safari/537.36";
-
-                let poe_url = tauri::WebviewUrl::External(tauri::Url::parse("https://poe.com").expect("Invalid URL"));
-                if let Ok(window) = tauri::WebviewWindow::builder(&app_handle, "poe", poe_url)
-                  .title("Poe")
-                  .resizable(true)
-                  .fullscreen(false)
-                  .inner_size(1440.0, 1080.0)
-                  .user_agent(user_agent)
-                  .additional_browser_args("--disable-blink-features=AutomationControlled --disable-features=IsolateOrigins,site-per-process --disable-site-isolation-trials --disable-web-security --allow-running-insecure-content --disable-blink-features=AutomationControlled")
-                  .build() {
-
-                  let _ = window.set_focus();
-
-                  // 添加页面加载完成事件监听器，用于自动登录和跟踪焦点
-                  let window_clone = window.clone();
-                  let app_handle_poe = app_handle.clone();
-
-                  window.on_window_event(move |event| {
-                    match event {
-                      WindowEvent::Focused(focused) => {
-                        // 更新窗口焦点状态
-                        if let Ok(mut state) = app_handle_poe.state::<Mutex<AppState>>().try_lock() {
-                          state.poe_focused = *focused;
-                        }
-
-                        // 如果窗口获得焦点，尝试自动登录
-                        if *focused {
-                          let window_clone2 = window_clone.clone();
-                          std::thread::spawn(move || {
-                            // 等待一段时间，确保页面完全加载
-                            std::thread::sleep(std::time::Duration::from_secs(2));
-
-                            // 注入浏览器模拟脚本
-                            let _ = inject_browser_emulation(window_clone2.clone());
-
-                            // 尝试自动登录
-                            let _ = auto_login(window_clone2, "poe".to_string());
-                          });
-                        }
-                      },
-                      _ => {}
-                    }
-                  });
-                }
+            id if id.contains(':') => {
+              // 切换窗口置顶或紧凑模式
+              let (mode, service) = id.split_once(':').unwrap_or_default();
+              if services::find(service).is_some() {
+                let modes = window_modes::get(&app_handle, service);
+                let result = match mode {
+                  "pin" => window_modes::set_pinned(&app_handle, service, !modes.pinned),
+                  "compact" => window_modes::set_compact(&app_handle, service, !modes.compact),
+                  "popover" => window_modes::set_popover(&app_handle, service, !modes.popover),
+                  "auto_hide" => window_modes::set_auto_hide(&app_handle, service, !modes.auto_hide),
+                  _ => Ok(()),
+                };
+                logging::log_err!(result, "切换窗口模式失败");
               }
+              logging::log_err!(tray::refresh(&app_handle), "更新托盘失败");
             }
             "settings" => {
               // 打开设置窗口
               if let Some(window) = app_handle.get_webview_window("settings") {
-                let _ = window.show();
-                let _ = window.set_focus();
+                logging::log_err!(window.show(), "显示窗口失败");
+                logging::log_err!(window.set_focus(), "聚焦窗口失败");
               } else {
                 // 如果设置窗口不存在，则创建它
                 let settings_url = tauri::WebviewUrl::App("settings.html".to_string().into());
-                if let Ok(window) = tauri::WebviewWindow::builder(&app_handle, "settings", settings_url)
-                  .title("AI Assistant 设置")
+                let result = tauri::WebviewWindow::builder(&app_handle, "settings", settings_url)
+                  .title(i18n::t(&app_handle, "settings-window-title"))
                   .resizable(true)
                   .fullscreen(false)
                   .inner_size(800.0, 600.0)
-                  .build() {
-
-                  let _ = window.set_focus();
-
-                  // 添加焦点事件监听器
-                  let app_handle_settings = app_handle.clone();
-                  window.on_window_event(move |event| {
-                    if let WindowEvent::Focused(focused) = event {
-                      // 更新窗口焦点状态
-                      if let Ok(mut state) = app_handle_settings.state::<Mutex<AppState>>().try_lock() {
-                        state.settings_focused = *focused;
+                  .build();
+                match result {
+                  Ok(window) => {
+                    // 恢复窗口位置和大小
+                    window_state::restore(&window);
+                    logging::log_err!(theme::apply(&window), "应用主题失败");
+                    logging::log_err!(window.set_focus(), "聚焦窗口失败");
+
+                    // 添加焦点事件监听器
+                    let app_handle_settings = app_handle.clone();
+                    window.on_window_event(move |event| {
+                      if let WindowEvent::Focused(focused) = event {
+                        // 更新窗口焦点状态
+                        app_handle_settings.state::<StateStore>().dispatch(StateEvent::WindowFocused {
+                          label: "settings".to_string(),
+                          focused: *focused,
+                        });
                       }
-                    }
-                  });
+                    });
+                  }
+                  Err(e) => tracing::error!(error = %e, "创建设置窗口失败"),
                 }
               }
             }
@@ -419,99 +851,135 @@ fn main() {
       let app_handle_clone = app.app_handle().clone();
 
       app.on_tray_icon_event(move |_tray, event| {
-        // 只处理左键点击事件
-        if let tauri::tray::TrayIconEvent::Click { button, .. } = event {
-          // 只处理左键点击
-          if button == tauri::tray::MouseButton::Left {
-            let app_handle = app_handle_clone.clone();
-
-            // 获取应用状态
-            let mut is_double_click = false;
-            let mut gemini_focused = false;
-            let mut poe_focused = false;
-            let mut settings_focused = false;
-
-            // 获取状态并检查是否是双击
-            if let Ok(mut state) = app_handle.state::<Mutex<AppState>>().try_lock() {
-              let now = Instant::now();
-              is_double_click = now.duration_since(state.last_tray_click_time).as_millis() < 300;
-              state.last_tray_click_time = now;
-
-              gemini_focused = state.gemini_focused;
-              poe_focused = state.poe_focused;
-              settings_focused = state.settings_focused;
-            }
-
-            // 获取所有窗口
-            let gemini_window = app_handle.get_webview_window("gemini");
-            let poe_window = app_handle.get_webview_window("poe");
-            let settings_window = app_handle.get_webview_window("settings");
-
-            // 检查是否有任何窗口可见
-            let gemini_visible = gemini_window.as_ref().map(|w| w.is_visible().unwrap_or(false)).unwrap_or(false);
-            let poe_visible = poe_window.as_ref().map(|w| w.is_visible().unwrap_or(false)).unwrap_or(false);
-            let settings_visible = settings_window.as_ref().map(|w| w.is_visible().unwrap_or(false)).unwrap_or(false);
-
-            let any_window_visible = gemini_visible || poe_visible || settings_visible;
-
-            if !any_window_visible {
-              // 如果没有窗口可见，则显示 Gemini 窗口
-              if let Some(window) = gemini_window {
-                let _ = window.show();
-                let _ = window.set_focus();
-              }
-            }
-            // 如果有窗口可见但没有窗口在前台，或者是双击，则隐藏所有窗口
-            else if !gemini_focused && !poe_focused && !settings_focused || is_double_click {
-              // 隐藏所有可见窗口
-              if gemini_visible {
-                if let Some(window) = gemini_window {
-                  let _ = window.hide();
-                }
+        // 每次点击会产生按下和松开两个事件，只处理松开
+        if let tauri::tray::TrayIconEvent::Click { button, button_state: MouseButtonState::Up, .. } = event {
+          let app_handle = app_handle_clone.clone();
+
+          // 记录左键点击并获取应用状态，用于判断是否是双击
+          let now = Instant::now();
+          let store = app_handle.state::<StateStore>();
+          let state = if button == MouseButton::Left {
+            store.dispatch(StateEvent::TrayClicked { at: now })
+          } else {
+            store.snapshot()
+          };
+          let interval = if button == MouseButton::Left { state.last_tray_click_interval } else { None };
+          let click = tray_policy::classify(button, interval);
+          let action = settings::get(&app_handle).tray_actions.action(click);
+
+          let snapshot = tray_policy::Snapshot {
+            windows: services::SERVICES
+              .iter()
+              .map(|s| s.id)
+              .chain(["settings"])
+              .map(|label| tray_policy::WindowSnapshot {
+                label: label.to_string(),
+                visible: app_handle
+                  .get_webview_window(label)
+                  .is_some_and(|w| w.is_visible().unwrap_or(false)),
+                focused: state.is_focused(label),
+                auto_hidden: state.recently_auto_hidden(label, now, tray_policy::REOPEN_GUARD),
+              })
+              .collect(),
+            pending_responses: state.pending_responses.clone(),
+            last_active: last_active_service(&app_handle),
+          };
+
+          let actions = tray_policy::decide(action, &snapshot);
+          let hidden = actions.iter().any(|a| matches!(a, TrayAction::Hide(_)));
+          for action in actions {
+            match action {
+              TrayAction::Show(service) => {
+                show_service_window(&app_handle, &service);
               }
-
-              if poe_visible {
-                if let Some(window) = poe_window {
-                  let _ = window.hide();
+              TrayAction::QuickAsk(service) => {
+                if let Some(window) = show_service_window(&app_handle, &service) {
+                  logging::log_err!(prompt::focus(&window), "聚焦输入框失败");
                 }
               }
-
-              if settings_visible {
-                if let Some(window) = settings_window {
-                  let _ = window.hide();
+              TrayAction::Focus(label) => {
+                if let Some(window) = app_handle.get_webview_window(&label) {
+                  logging::log_err!(window.set_focus(), "聚焦窗口失败");
                 }
               }
-            }
-            // 如果有窗口可见且有窗口在前台，则将窗口置于前台
-            else {
-              // 将可见窗口置于前台
-              if gemini_visible {
-                if let Some(window) = gemini_window {
-                  let _ = window.set_focus();
-                }
-              } else if poe_visible {
-                if let Some(window) = poe_window {
-                  let _ = window.set_focus();
-                }
-              } else if settings_visible {
-                if let Some(window) = settings_window {
-                  let _ = window.set_focus();
+              TrayAction::Hide(label) => {
+                if let Some(window) = app_handle.get_webview_window(&label) {
+                  logging::log_err!(window_state::hide(&window), "隐藏窗口失败");
                 }
               }
             }
           }
+
+          if hidden {
+            logging::log_err!(tray::refresh(&app_handle), "更新托盘失败");
+          }
         }
       });
 
       Ok(())
     })
     .on_window_event(|window, event| {
-      if let WindowEvent::CloseRequested { api, .. } = event {
-        // 当用户点击关闭按钮时，隐藏窗口而不是退出应用
-        let _ = window.hide();
-        api.prevent_close();
+      match event {
+        WindowEvent::CloseRequested { api, .. } => {
+          let app_handle = window.app_handle();
+          match settings::get(app_handle).close_behavior {
+            CloseBehavior::Quit => {
+              api.prevent_close();
+              shutdown::quit(app_handle);
+            }
+            CloseBehavior::Close => {
+              // 关闭窗口并释放网页，窗口销毁后更新托盘
+              logging::log_err!(window_state::save(window, false), "保存窗口状态失败");
+            }
+            CloseBehavior::Hide => {
+              // 隐藏窗口而不是退出应用
+              logging::log_err!(window_state::save(window, false), "保存窗口状态失败");
+              logging::log_err!(window.hide(), "隐藏窗口失败");
+              api.prevent_close();
+              logging::log_err!(tray::refresh(app_handle), "更新托盘失败");
+
+              // 第一次隐藏时提示应用仍在托盘中运行
+              if !session::tray_notice_shown(app_handle) {
+                logging::log_err!(notifications::notify_running_in_tray(app_handle), "发送通知失败");
+                logging::log_err!(session::set_tray_notice_shown(app_handle), "记录提示状态失败");
+              }
+            }
+          }
+        }
+        WindowEvent::Destroyed => {
+          // 窗口关闭后清除焦点状态并更新托盘
+          window.app_handle().state::<StateStore>().dispatch(StateEvent::WindowFocused {
+            label: window.label().to_string(),
+            focused: false,
+          });
+          logging::log_err!(tray::refresh(window.app_handle()), "更新托盘失败");
+        }
+        WindowEvent::Moved(_) | WindowEvent::Resized(_) | WindowEvent::Focused(true) => {
+          // 记录窗口位置、大小和可见性
+          let visible = window.is_visible().unwrap_or(true);
+          logging::log_err!(window_state::save(window, visible), "保存窗口状态失败");
+        }
+        WindowEvent::Focused(false) if services::find(window.label()).is_some() => {
+          // 设置了自动隐藏或处于弹出模式的服务窗口失去焦点时自动隐藏
+          if let Some(window) = window.app_handle().get_webview_window(window.label()) {
+            auto_hide::on_focus_lost(window);
+          }
+        }
+        WindowEvent::ThemeChanged(_) => {
+          // 系统主题变化后，跟随系统的窗口需要更新深色样式
+          if let Some(window) = window.app_handle().get_webview_window(window.label()) {
+            logging::log_err!(theme::apply_page_style(&window), "应用深色样式失败");
+          }
+        }
+        _ => {}
       }
     })
-    .run(tauri::generate_context!())
-    .expect("error while running tauri application");
+    .build(tauri::generate_context!())
+    .expect("error while building tauri application")
+    .run(|_app_handle, event| {
+      // 所有窗口都关闭后继续在托盘中运行，只有主动退出（带退出码）时才退出
+      if let tauri::RunEvent::ExitRequested { code: None, api, .. } = event {
+        api.prevent_exit();
+      }
+    });
 }
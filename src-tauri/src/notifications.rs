@@ -0,0 +1,63 @@
+use crate::{i18n, logging, services};
+use notify_rust::Notification;
+use tauri::{AppHandle, Runtime};
+
+// 通知正文的最大长度（字符数）
+const MAX_BODY_CHARS: usize = 120;
+
+// 第一次关闭窗口时提示应用仍在托盘中运行
+pub fn notify_running_in_tray<R: Runtime>(app_handle: &AppHandle<R>) -> Result<(), String> {
+    Notification::new()
+        .appname("AI Assistant")
+        .summary(&i18n::t(app_handle, "notification-tray-title"))
+        .body(&i18n::t(app_handle, "notification-tray-body"))
+        .show()
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+// 回答生成完成后发出桌面通知，正文为回答的第一行
+pub fn notify_response<R: Runtime>(app_handle: &AppHandle<R>, service: &str, response: &str) -> Result<(), String> {
+    let name = services::find(service).map(|s| s.name).unwrap_or(service);
+    let body: String = match response.lines().map(str::trim).find(|line| !line.is_empty()) {
+        Some(first_line) => first_line.chars().take(MAX_BODY_CHARS).collect(),
+        None => i18n::t(app_handle, "notification-response-fallback"),
+    };
+
+    let mut notification = Notification::new();
+    notification
+        .appname("AI Assistant")
+        .summary(&i18n::t_args(app_handle, "notification-response-finished", &[("service", name)]))
+        .body(&body);
+
+    // Linux 上点击通知时切换到对应的服务窗口
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        notification.action("default", &i18n::t(app_handle, "notification-open"));
+        let handle = notification.show().map_err(|e| e.to_string())?;
+
+        let app_handle = app_handle.clone();
+        let service = service.to_string();
+        std::thread::spawn(move || {
+            handle.wait_for_action(|action| {
+                if action == "default" {
+                    let app_handle_clone = app_handle.clone();
+                    let result = app_handle.run_on_main_thread(move || {
+                        crate::show_service_window(&app_handle_clone, &service);
+                    });
+                    logging::log_err!(result, "切换到服务窗口失败");
+                }
+            });
+        });
+    }
+
+    // 其他平台的通知不支持点击回调，点击托盘图标时会优先打开有未查看回答的服务
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    {
+        let _ = app_handle;
+        notification.show().map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use tauri::{Runtime, WebviewWindow};
+
+// 向页面注入（或移除）一段带 ID 的样式，重复调用时会替换已有的样式
+pub fn apply<R: Runtime>(window: &WebviewWindow<R>, id: &str, css: Option<&str>) -> Result<(), String> {
+    let id = serde_json::to_string(id).map_err(|e| e.to_string())?;
+    let css = serde_json::to_string(&css).map_err(|e| e.to_string())?;
+
+    let script = format!(
+        r#"
+        (function() {{
+            const id = {};
+            const css = {};
+            let style = document.getElementById(id);
+
+            if (css === null) {{
+                if (style) {{
+                    style.remove();
+                }}
+                return;
+            }}
+
+            if (!style) {{
+                style = document.createElement('style');
+                style.id = id;
+                (document.head || document.documentElement).appendChild(style);
+            }}
+            style.textContent = css;
+        }})()
+        "#,
+        id, css
+    );
+
+    window.eval(&script).map_err(|e| e.to_string())
+}
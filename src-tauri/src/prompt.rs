@@ -0,0 +1,79 @@
+use crate::services;
+use tauri::{Runtime, WebviewWindow};
+
+// 将提示词填入服务的输入框，可选择直接发送
+pub fn fill<R: Runtime>(window: &WebviewWindow<R>, text: &str, submit: bool) -> Result<(), String> {
+    let service = services::find(window.label()).ok_or_else(|| crate::i18n::tr("error-unsupported-service", &[("service", window.label())]))?;
+
+    let input_selector = serde_json::to_string(service.input_selector).map_err(|e| e.to_string())?;
+    let send_selector = serde_json::to_string(service.send_selector).map_err(|e| e.to_string())?;
+    let text = serde_json::to_string(text).map_err(|e| e.to_string())?;
+
+    let script = format!(
+        r#"
+        (function() {{
+            const inputSelector = {};
+            const sendSelector = {};
+            const text = {};
+            const submit = {};
+            let attempts = 0;
+
+            // 页面可能仍在加载，等待输入框出现
+            function tryFill() {{
+                const input = document.querySelector(inputSelector);
+                if (!input) {{
+                    if (++attempts < 30) {{
+                        setTimeout(tryFill, 500);
+                    }}
+                    return;
+                }}
+
+                input.focus();
+                if (input.tagName === 'TEXTAREA') {{
+                    // 使用原生 setter，以便 React 等框架感知到输入变化
+                    const setter = Object.getOwnPropertyDescriptor(HTMLTextAreaElement.prototype, 'value').set;
+                    setter.call(input, text);
+                    input.dispatchEvent(new Event('input', {{ bubbles: true }}));
+                }} else {{
+                    document.execCommand('selectAll', false, null);
+                    document.execCommand('insertText', false, text);
+                }}
+
+                if (submit) {{
+                    setTimeout(() => {{
+                        const sendButton = document.querySelector(sendSelector);
+                        if (sendButton) {{
+                            sendButton.click();
+                        }}
+                    }}, 300);
+                }}
+            }}
+
+            tryFill();
+        }})()
+        "#,
+        input_selector, send_selector, text, submit
+    );
+
+    window.eval(&script).map_err(|e| e.to_string())
+}
+
+// 聚焦服务的输入框，用于快速提问
+pub fn focus<R: Runtime>(window: &WebviewWindow<R>) -> Result<(), String> {
+    let service = services::find(window.label()).ok_or_else(|| crate::i18n::tr("error-unsupported-service", &[("service", window.label())]))?;
+    let input_selector = serde_json::to_string(service.input_selector).map_err(|e| e.to_string())?;
+
+    let script = format!(
+        r#"
+        (function() {{
+            const input = document.querySelector({});
+            if (input) {{
+                input.focus();
+            }}
+        }})()
+        "#,
+        input_selector
+    );
+
+    window.eval(&script).map_err(|e| e.to_string())
+}
@@ -0,0 +1,498 @@
+use crate::cli::ExportFormat;
+use crate::{control, i18n, logging};
+use interprocess::local_socket::{prelude::*, ListenerOptions, Stream};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, BufReader, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, Runtime};
+
+// JSON-RPC 标准错误码
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+// 应用自定义错误码
+const APP_ERROR: i64 = -32000;
+const UNAUTHORIZED: i64 = -32001;
+
+// 连接编号，用于在连接断开时取消订阅
+static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(1);
+
+// 订阅了事件的连接
+#[derive(Default)]
+pub struct Subscribers(Mutex<Vec<(u64, Sender<Value>)>>);
+
+// 定义 JSON-RPC 请求
+#[derive(Debug, Deserialize)]
+struct RpcRequest {
+    jsonrpc: String,
+    // 没有 ID 的请求是通知，不需要响应
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+// 定义 JSON-RPC 错误
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+// 定义请求参数结构体
+#[derive(Deserialize)]
+struct AuthenticateParams {
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct ServiceParams {
+    service: String,
+}
+
+#[derive(Deserialize)]
+struct SendPromptParams {
+    service: String,
+    prompt: String,
+    // 是否直接发送，默认发送
+    #[serde(default = "default_submit")]
+    submit: bool,
+}
+
+#[derive(Deserialize)]
+struct ExportParams {
+    service: String,
+    #[serde(default)]
+    format: ExportFormat,
+}
+
+fn default_submit() -> bool {
+    true
+}
+
+// 单个连接的状态
+struct Connection {
+    id: u64,
+    // 是否已通过令牌验证
+    authenticated: bool,
+    // 响应和事件通知都通过这里交给写线程发送
+    sender: Sender<Value>,
+}
+
+// 开始监听控制接口的连接，套接字文件只允许当前用户访问
+#[cfg(unix)]
+pub fn listen<R: Runtime>(app_handle: AppHandle<R>) -> io::Result<()> {
+    use interprocess::local_socket::GenericFilePath;
+    use interprocess::os::unix::local_socket::ListenerOptionsExt;
+
+    // 单实例检查已经通过，当前用户的残留套接字文件一定来自上次异常退出
+    let path = crate::ipc::socket_path(&format!("{}-control", crate::ipc::SOCKET_NAME))?;
+    if path.exists() {
+        crate::ipc::check_owner(&path, crate::ipc::current_uid())?;
+        std::fs::remove_file(&path)?;
+    }
+
+    let listener = ListenerOptions::new()
+        .name(path.to_fs_name::<GenericFilePath>()?)
+        .mode(0o600)
+        .create_sync()?;
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming().filter_map(Result::ok) {
+            let app_handle = app_handle.clone();
+            std::thread::spawn(move || {
+                logging::log_err!(handle_connection(&app_handle, stream), "处理连接失败");
+            });
+        }
+    });
+
+    Ok(())
+}
+
+// 其他平台无法用文件权限限制访问，暂不提供控制接口
+#[cfg(not(unix))]
+pub fn listen<R: Runtime>(_app_handle: AppHandle<R>) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, i18n::tr("error-control-unsupported", &[])))
+}
+
+// 向所有订阅了事件的连接发送通知
+pub fn publish<R: Runtime>(app_handle: &AppHandle<R>, event: &str, data: Value) {
+    let Some(subscribers) = app_handle.try_state::<Subscribers>() else {
+        return;
+    };
+    let Ok(mut subscribers) = subscribers.0.lock() else {
+        return;
+    };
+
+    let notification = json!({
+        "jsonrpc": "2.0",
+        "method": "event",
+        "params": { "event": event, "data": data },
+    });
+
+    // 发送失败说明连接已经断开
+    subscribers.retain(|(_, sender)| sender.send(notification.clone()).is_ok());
+}
+
+fn unsubscribe<R: Runtime>(app_handle: &AppHandle<R>, connection_id: u64) {
+    if let Ok(mut subscribers) = app_handle.state::<Subscribers>().0.lock() {
+        subscribers.retain(|(id, _)| *id != connection_id);
+    }
+}
+
+fn handle_connection<R: Runtime>(app_handle: &AppHandle<R>, stream: Stream) -> io::Result<()> {
+    let stream = Arc::new(stream);
+    let (sender, receiver) = mpsc::channel::<Value>();
+
+    // 响应和事件通知由同一个线程按顺序写出，避免输出交错
+    let writer_stream = stream.clone();
+    let writer = std::thread::spawn(move || {
+        for message in receiver {
+            let mut line = message.to_string();
+            line.push('\n');
+            if (&*writer_stream).write_all(line.as_bytes()).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut connection = Connection {
+        id: NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed),
+        authenticated: control::authorize(app_handle, None),
+        sender,
+    };
+
+    let mut result = Ok(());
+    for line in BufReader::new(&*stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(response) = handle_message(app_handle, &mut connection, &line) {
+            if connection.sender.send(response).is_err() {
+                break;
+            }
+        }
+    }
+
+    // 连接断开后取消订阅，写线程在所有发送端释放后退出
+    unsubscribe(app_handle, connection.id);
+    drop(connection);
+    let _ = writer.join();
+
+    result
+}
+
+// 处理一行 JSON-RPC 消息，返回要发送的响应；通知不需要响应
+fn handle_message<R: Runtime>(app_handle: &AppHandle<R>, connection: &mut Connection, line: &str) -> Option<Value> {
+    let value = match serde_json::from_str::<Value>(line) {
+        Ok(value) => value,
+        Err(e) => return Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, e.to_string()))),
+    };
+
+    let request = match serde_json::from_value::<RpcRequest>(value) {
+        Ok(request) if request.jsonrpc == "2.0" => request,
+        Ok(request) => {
+            let error = RpcError::new(INVALID_REQUEST, i18n::tr("error-jsonrpc-version", &[]));
+            return Some(error_response(request.id.unwrap_or(Value::Null), error));
+        }
+        Err(e) => return Some(error_response(Value::Null, RpcError::new(INVALID_REQUEST, e.to_string()))),
+    };
+
+    let result = dispatch(app_handle, connection, &request.method, request.params);
+    let id = request.id?;
+
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => error_response(id, error),
+    })
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn app_error(message: String) -> RpcError {
+    RpcError::new(APP_ERROR, message)
+}
+
+// 执行方法调用，与 Tauri 命令和命令行子命令共用 control 模块中的命令实现
+fn dispatch<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    connection: &mut Connection,
+    method: &str,
+    params: Value,
+) -> Result<Value, RpcError> {
+    if method == "authenticate" {
+        let params: AuthenticateParams = parse_params(params)?;
+        connection.authenticated = control::authorize(app_handle, Some(&params.token));
+
+        return if connection.authenticated {
+            Ok(json!({ "authenticated": true }))
+        } else {
+            Err(RpcError::new(UNAUTHORIZED, i18n::tr("error-invalid-token", &[])))
+        };
+    }
+
+    // 设置了令牌时，必须先调用 authenticate
+    if !connection.authenticated {
+        return Err(RpcError::new(UNAUTHORIZED, i18n::tr("error-authentication-required", &[])));
+    }
+
+    let app_handle = app_handle.clone();
+    match method {
+        "list_services" => serde_json::to_value(control::list_services(app_handle)).map_err(|e| app_error(e.to_string())),
+        "show" => {
+            let params: ServiceParams = parse_params(params)?;
+            block_on(control::show_service(app_handle, params.service.clone()))?;
+            Ok(json!({ "service": params.service }))
+        }
+        "hide" => Ok(json!({ "hidden": control::hide_windows(app_handle) })),
+        "send_prompt" => {
+            let params: SendPromptParams = parse_params(params)?;
+            block_on(control::send_prompt(app_handle, params.service.clone(), params.prompt, Some(params.submit)))?;
+            Ok(json!({ "service": params.service }))
+        }
+        "get_last_response" => {
+            let params: ServiceParams = parse_params(params)?;
+            let response = control::get_last_response(app_handle, params.service.clone()).map_err(app_error)?;
+            Ok(json!({ "service": params.service, "response": response }))
+        }
+        "export_conversation" => {
+            let params: ExportParams = parse_params(params)?;
+            block_on(control::export_conversation(app_handle, params.service, Some(params.format)))
+        }
+        "subscribe" => {
+            let subscribers = app_handle.state::<Subscribers>();
+            let mut subscribers = subscribers.0.lock().map_err(|e| app_error(e.to_string()))?;
+            if !subscribers.iter().any(|(id, _)| *id == connection.id) {
+                subscribers.push((connection.id, connection.sender.clone()));
+            }
+            Ok(json!({ "subscribed": true }))
+        }
+        "unsubscribe" => {
+            unsubscribe(&app_handle, connection.id);
+            Ok(json!({ "subscribed": false }))
+        }
+        _ => Err(RpcError::new(METHOD_NOT_FOUND, i18n::tr("error-unknown-method", &[("method", method)]))),
+    }
+}
+
+// 在当前线程上等待异步命令完成；连接在各自的线程上处理，不会阻塞主线程
+fn block_on<T, F: std::future::Future<Output = Result<T, String>>>(future: F) -> Result<T, RpcError> {
+    tauri::async_runtime::block_on(future).map_err(app_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_state::{StateEvent, StateStore};
+    use crate::archive::Archive;
+    use crate::test_support::{mock_app, set_control_token};
+    use std::sync::mpsc::Receiver;
+    use tauri::test::MockRuntime;
+
+    fn connect(app_handle: &AppHandle<MockRuntime>) -> (Connection, Receiver<Value>) {
+        let (sender, receiver) = mpsc::channel();
+        let connection = Connection {
+            id: NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed),
+            authenticated: control::authorize(app_handle, None),
+            sender,
+        };
+
+        (connection, receiver)
+    }
+
+    fn call(app_handle: &AppHandle<MockRuntime>, connection: &mut Connection, method: &str, params: Value) -> Value {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        handle_message(app_handle, connection, &request.to_string()).expect("request with an id has a response")
+    }
+
+    fn error_code(response: &Value) -> Option<i64> {
+        response["error"]["code"].as_i64()
+    }
+
+    #[test]
+    fn requests_are_allowed_when_no_token_is_set() {
+        let app = mock_app();
+        let (mut connection, _) = connect(app.handle());
+
+        let response = call(app.handle(), &mut connection, "list_services", Value::Null);
+
+        let ids: Vec<&str> = response["result"].as_array().unwrap().iter().map(|s| s["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["gemini", "poe"]);
+    }
+
+    #[test]
+    fn token_is_required_when_set() {
+        let app = mock_app();
+        set_control_token(app.handle(), "secret");
+        let (mut connection, _) = connect(app.handle());
+
+        let response = call(app.handle(), &mut connection, "list_services", Value::Null);
+        assert_eq!(error_code(&response), Some(UNAUTHORIZED));
+
+        let response = call(app.handle(), &mut connection, "authenticate", json!({ "token": "wrong" }));
+        assert_eq!(error_code(&response), Some(UNAUTHORIZED));
+        let response = call(app.handle(), &mut connection, "hide", Value::Null);
+        assert_eq!(error_code(&response), Some(UNAUTHORIZED));
+
+        let response = call(app.handle(), &mut connection, "authenticate", json!({ "token": "secret" }));
+        assert_eq!(response["result"], json!({ "authenticated": true }));
+        let response = call(app.handle(), &mut connection, "list_services", Value::Null);
+        assert!(response["result"].is_array());
+    }
+
+    #[test]
+    fn show_creates_the_service_window() {
+        let app = mock_app();
+        let (mut connection, _) = connect(app.handle());
+
+        let response = call(app.handle(), &mut connection, "show", json!({ "service": "poe" }));
+
+        assert_eq!(response["result"], json!({ "service": "poe" }));
+        assert!(app.get_webview_window("poe").is_some());
+        assert!(app.get_webview_window("gemini").is_none());
+    }
+
+    #[test]
+    fn show_rejects_unknown_services() {
+        let app = mock_app();
+        let (mut connection, _) = connect(app.handle());
+
+        let response = call(app.handle(), &mut connection, "show", json!({ "service": "unknown" }));
+
+        assert_eq!(error_code(&response), Some(APP_ERROR));
+    }
+
+    #[test]
+    fn hide_returns_the_hidden_windows() {
+        let app = mock_app();
+        let (mut connection, _) = connect(app.handle());
+        call(app.handle(), &mut connection, "show", json!({ "service": "gemini" }));
+
+        let response = call(app.handle(), &mut connection, "hide", Value::Null);
+
+        assert_eq!(response["result"], json!({ "hidden": ["gemini"] }));
+    }
+
+    #[test]
+    fn send_prompt_opens_the_service() {
+        let app = mock_app();
+        let (mut connection, _) = connect(app.handle());
+
+        let params = json!({ "service": "gemini", "prompt": "hello", "submit": false });
+        let response = call(app.handle(), &mut connection, "send_prompt", params);
+
+        assert_eq!(response["result"], json!({ "service": "gemini" }));
+        assert!(app.get_webview_window("gemini").is_some());
+
+        let response = call(app.handle(), &mut connection, "send_prompt", json!({ "service": "gemini" }));
+        assert_eq!(error_code(&response), Some(INVALID_PARAMS));
+    }
+
+    #[test]
+    fn get_last_response_returns_the_latest_answer() {
+        let app = mock_app();
+        let (mut connection, _) = connect(app.handle());
+
+        let response = call(app.handle(), &mut connection, "get_last_response", json!({ "service": "poe" }));
+        assert_eq!(response["result"], json!({ "service": "poe", "response": null }));
+
+        app.state::<StateStore>().dispatch(StateEvent::GenerationFinished {
+            service: "poe".to_string(),
+            response: "answer".to_string(),
+            hidden: false,
+        });
+        let response = call(app.handle(), &mut connection, "get_last_response", json!({ "service": "poe" }));
+        assert_eq!(response["result"], json!({ "service": "poe", "response": "answer" }));
+    }
+
+    #[test]
+    fn export_conversation_reads_the_archive() {
+        let app = mock_app();
+        let (mut connection, _) = connect(app.handle());
+
+        let response = call(app.handle(), &mut connection, "export_conversation", json!({ "service": "poe" }));
+        assert_eq!(error_code(&response), Some(APP_ERROR));
+
+        call(app.handle(), &mut connection, "show", json!({ "service": "poe" }));
+        let url = app.get_webview_window("poe").unwrap().url().unwrap();
+        app.state::<Archive>().record("poe", url.as_str(), "Title", "content").unwrap();
+
+        let response = call(app.handle(), &mut connection, "export_conversation", json!({ "service": "poe" }));
+        assert_eq!(response["result"]["format"], "md");
+        assert_eq!(response["result"]["content"], format!("# Title\n\n<{}>\n\ncontent\n", url));
+
+        let params = json!({ "service": "poe", "format": "json" });
+        let response = call(app.handle(), &mut connection, "export_conversation", params);
+        assert_eq!(response["result"]["content"], "content");
+    }
+
+    #[test]
+    fn subscribers_receive_events_until_they_unsubscribe() {
+        let app = mock_app();
+        let (mut connection, receiver) = connect(app.handle());
+
+        let response = call(app.handle(), &mut connection, "subscribe", Value::Null);
+        assert_eq!(response["result"], json!({ "subscribed": true }));
+
+        publish(app.handle(), "generation_started", json!({ "service": "poe" }));
+        let notification = receiver.try_recv().unwrap();
+        assert_eq!(notification["method"], "event");
+        assert_eq!(notification["params"]["event"], "generation_started");
+
+        call(app.handle(), &mut connection, "unsubscribe", Value::Null);
+        publish(app.handle(), "generation_started", json!({ "service": "poe" }));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn invalid_messages_are_rejected() {
+        let app = mock_app();
+        let (mut connection, _) = connect(app.handle());
+
+        let response = handle_message(app.handle(), &mut connection, "not json").unwrap();
+        assert_eq!(error_code(&response), Some(PARSE_ERROR));
+
+        let request = json!({ "jsonrpc": "1.0", "id": 1, "method": "hide" }).to_string();
+        let response = handle_message(app.handle(), &mut connection, &request).unwrap();
+        assert_eq!(error_code(&response), Some(INVALID_REQUEST));
+
+        let response = call(app.handle(), &mut connection, "unknown", Value::Null);
+        assert_eq!(error_code(&response), Some(METHOD_NOT_FOUND));
+
+        // 通知没有 ID，不需要响应
+        let request = json!({ "jsonrpc": "2.0", "method": "hide" }).to_string();
+        assert!(handle_message(app.handle(), &mut connection, &request).is_none());
+    }
+}
@@ -0,0 +1,63 @@
+// 服务窗口使用的 User-Agent，避免服务页面因浏览器版本拒绝访问
+pub const USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/125.0.0.0 Safari/537.36";
+
+// 服务窗口的浏览器参数
+pub const BROWSER_ARGS: &str = "--disable-blink-features=AutomationControlled --disable-features=IsolateOrigins,site-per-process --disable-site-isolation-trials --disable-web-security --allow-running-insecure-content --disable-blink-features=AutomationControlled";
+
+// 定义服务结构体
+pub struct ServiceDef {
+    // 服务 ID，同时也是窗口标签
+    pub id: &'static str,
+    // 显示名称
+    pub name: &'static str,
+    // 服务首页
+    pub home_url: &'static str,
+    // 紧凑模式下用于隐藏侧边栏的 CSS
+    pub compact_css: &'static str,
+    // 深色模式下注入的 CSS，服务自带深色主题时为空
+    pub dark_css: &'static str,
+    // 托盘图标（PNG）
+    pub icon: &'static [u8],
+    // 提示词输入框的选择器
+    pub input_selector: &'static str,
+    // 发送按钮的选择器
+    pub send_selector: &'static str,
+}
+
+// 支持的服务列表
+pub const SERVICES: &[ServiceDef] = &[
+    ServiceDef {
+        id: "gemini",
+        name: "Gemini",
+        home_url: "https://gemini.google.com/app",
+        compact_css: "bard-sidenav, side-navigation-v2, mat-sidenav { display: none !important; }",
+        // Gemini 跟随 prefers-color-scheme 切换自带的深色主题
+        dark_css: "",
+        icon: include_bytes!("../icons/gemini.png"),
+        input_selector: "rich-textarea .ql-editor, div[contenteditable=\"true\"]",
+        send_selector: "button.send-button, button[aria-label=\"Send message\"]",
+    },
+    ServiceDef {
+        id: "poe",
+        name: "Poe",
+        home_url: "https://poe.com",
+        compact_css: "[class*=\"SidebarLayout_sidebar\"], [class*=\"SidebarLayout_left\"] { display: none !important; }",
+        dark_css: "html { filter: invert(0.9) hue-rotate(180deg); background: #fff; } img, video, picture, canvas, [style*=\"background-image\"] { filter: invert(1) hue-rotate(180deg); }",
+        icon: include_bytes!("../icons/poe.png"),
+        input_selector: "textarea[class*=\"GrowingTextArea_textArea\"], textarea",
+        send_selector: "button[class*=\"ChatMessageSendButton\"], button[data-button-send]",
+    },
+];
+
+// 根据 ID 查找服务
+pub fn find(id: &str) -> Option<&'static ServiceDef> {
+    SERVICES.iter().find(|service| service.id == id)
+}
+
+impl ServiceDef {
+    // 判断 URL 是否属于该服务的站点（登录跳转等第三方页面不算）
+    pub fn owns_url(&self, url: &tauri::Url) -> bool {
+        let home = tauri::Url::parse(self.home_url).expect("Invalid URL");
+        url.scheme() == "https" && url.host_str() == home.host_str()
+    }
+}
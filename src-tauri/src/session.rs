@@ -0,0 +1,89 @@
+use crate::{services, settings};
+use serde_json::json;
+use tauri::{AppHandle, Manager, Runtime, Url, WebviewWindow};
+use tauri_plugin_store::StoreExt;
+
+// 会话状态（如各服务上次打开的 URL）
+pub const SESSION_STORE: &str = "session.json";
+
+// 记录服务窗口当前的 URL
+pub fn record_url<R: Runtime>(app_handle: &AppHandle<R>, service: &str, url: &Url) -> Result<(), String> {
+    // 只记录服务自身站点的页面
+    match services::find(service) {
+        Some(def) if def.owns_url(url) => {}
+        _ => return Ok(()),
+    }
+
+    let store = app_handle.store(SESSION_STORE).map_err(|e| e.to_string())?;
+    let mut last_urls = store
+        .get("last_urls")
+        .filter(|v| v.is_object())
+        .unwrap_or_else(|| json!({}));
+    if last_urls.get(service).and_then(|v| v.as_str()) == Some(url.as_str()) {
+        return Ok(());
+    }
+
+    last_urls[service] = json!(url.as_str());
+    store.set("last_urls", last_urls);
+
+    Ok(())
+}
+
+// 记录窗口当前的 URL（单页应用内的跳转不会触发页面加载事件）
+pub fn record_window_url<R: Runtime>(window: &WebviewWindow<R>) -> Result<(), String> {
+    let url = window.url().map_err(|e| e.to_string())?;
+    record_url(window.app_handle(), window.label(), &url)
+}
+
+// 获取服务启动时应打开的 URL
+pub fn restore_url<R: Runtime>(app_handle: &AppHandle<R>, service: &str) -> Option<Url> {
+    if !settings::get(app_handle).restore_last_url {
+        return None;
+    }
+
+    let store = app_handle.store(SESSION_STORE).ok()?;
+    let url = store.get("last_urls")?.get(service)?.as_str()?.to_string();
+    let url = Url::parse(&url).ok()?;
+
+    // 防止恢复到不属于该服务的页面
+    services::find(service).filter(|def| def.owns_url(&url))?;
+
+    Some(url)
+}
+
+// 获取上次使用的服务
+pub fn last_service<R: Runtime>(app_handle: &AppHandle<R>) -> Option<String> {
+    let store = app_handle.store(SESSION_STORE).ok()?;
+    let service = store.get("last_service")?.as_str()?.to_string();
+    services::find(&service)?;
+
+    Some(service)
+}
+
+// 是否已经提示过关闭窗口后应用仍在托盘中运行
+pub fn tray_notice_shown<R: Runtime>(app_handle: &AppHandle<R>) -> bool {
+    app_handle
+        .store(SESSION_STORE)
+        .ok()
+        .and_then(|store| store.get("tray_notice_shown"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+// 记录已经提示过应用仍在托盘中运行
+pub fn set_tray_notice_shown<R: Runtime>(app_handle: &AppHandle<R>) -> Result<(), String> {
+    let store = app_handle.store(SESSION_STORE).map_err(|e| e.to_string())?;
+    store.set("tray_notice_shown", true);
+
+    Ok(())
+}
+
+// 记录上次使用的服务
+pub fn set_last_service<R: Runtime>(app_handle: &AppHandle<R>, service: &str) -> Result<(), String> {
+    let store = app_handle.store(SESSION_STORE).map_err(|e| e.to_string())?;
+    if store.get("last_service").and_then(|v| v.as_str().map(str::to_string)).as_deref() != Some(service) {
+        store.set("last_service", service);
+    }
+
+    Ok(())
+}
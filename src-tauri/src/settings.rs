@@ -0,0 +1,499 @@
+use crate::services;
+use crate::tray_policy::TrayActions;
+use crate::window_modes::{self, WindowModes};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_store::StoreExt;
+
+// 用户设置
+pub const SETTINGS_STORE: &str = "settings.json";
+
+// 设置变化时发出的事件
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+// 当前的设置格式版本
+const CURRENT_VERSION: u64 = 2;
+
+// 定义界面语言设置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    // 跟随系统
+    #[default]
+    #[serde(rename = "system")]
+    System,
+    #[serde(rename = "en")]
+    En,
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+}
+
+// 定义主题设置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeSetting {
+    // 跟随系统
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+// 定义点击窗口关闭按钮时的行为
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseBehavior {
+    // 隐藏到托盘
+    #[default]
+    Hide,
+    // 关闭窗口并释放网页，再次打开时重新加载
+    Close,
+    // 退出应用
+    Quit,
+}
+
+// 定义设置结构体
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // 首次启动时显示的服务
+    pub default_service: String,
+    // 启动时是否恢复上次打开的页面
+    pub restore_last_url: bool,
+    // 各服务的窗口模式
+    pub window_modes: BTreeMap<String, WindowModes>,
+    // 紧凑模式下是否隐藏服务的侧边栏
+    pub compact_hide_sidebar: bool,
+    // 本地控制接口的访问令牌，未设置时不要求令牌
+    pub control_token: Option<String>,
+    // 界面语言
+    pub language: Language,
+    // 窗口主题
+    pub theme: ThemeSetting,
+    // 日志级别，可以按模块设置，例如 `info,app::rpc=debug`
+    pub log_filter: String,
+    // 点击托盘图标时执行的操作
+    pub tray_actions: TrayActions,
+    // 点击窗口关闭按钮时的行为
+    pub close_behavior: CloseBehavior,
+    // 登录时自动启动
+    pub launch_at_login: bool,
+    // 启动时不显示窗口，只在托盘中运行
+    pub start_hidden: bool,
+    // 启动后在后台预先创建的服务窗口
+    pub preload_services: Vec<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            default_service: services::SERVICES[0].id.to_string(),
+            restore_last_url: true,
+            window_modes: BTreeMap::new(),
+            compact_hide_sidebar: true,
+            control_token: None,
+            language: Language::System,
+            theme: ThemeSetting::System,
+            log_filter: "info".to_string(),
+            tray_actions: TrayActions::default(),
+            close_behavior: CloseBehavior::Hide,
+            launch_at_login: false,
+            start_hidden: false,
+            preload_services: Vec::new(),
+        }
+    }
+}
+
+// 串行化设置的读取、修改和保存，避免同时修改设置时后保存的一方覆盖另一方的修改
+#[derive(Default)]
+pub struct SettingsLock(Mutex<()>);
+
+// 定义设置变化事件的内容，只包含变化的字段名，避免把令牌等内容广播给服务页面
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsChanged {
+    pub changed: Vec<String>,
+}
+
+impl Settings {
+    // 获取服务的窗口模式
+    pub fn window_modes(&self, service: &str) -> WindowModes {
+        self.window_modes.get(service).copied().unwrap_or_default()
+    }
+
+    // 检查设置是否有效
+    pub fn validate(&self) -> Result<(), String> {
+        if services::find(&self.default_service).is_none() {
+            return Err(crate::i18n::tr("error-unsupported-service", &[("service", &self.default_service)]));
+        }
+        if let Some(service) = self.window_modes.keys().find(|s| services::find(s).is_none()) {
+            return Err(crate::i18n::tr("error-unsupported-service", &[("service", service)]));
+        }
+        if let Some(service) = self.preload_services.iter().find(|s| services::find(s).is_none()) {
+            return Err(crate::i18n::tr("error-unsupported-service", &[("service", service)]));
+        }
+        if self.control_token.as_deref().is_some_and(|token| token.chars().any(char::is_whitespace)) {
+            return Err(crate::i18n::tr("error-token-whitespace", &[]));
+        }
+        crate::logging::validate_filter(&self.log_filter)?;
+
+        Ok(())
+    }
+
+    // 规范化设置：空令牌视为未设置，丢弃与默认值相同的窗口模式和重复的预加载服务
+    fn normalize(&mut self) {
+        self.control_token = self
+            .control_token
+            .take()
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty());
+        self.window_modes.retain(|_, modes| *modes != WindowModes::default());
+        let mut seen = Vec::new();
+        self.preload_services.retain(|service| {
+            let first = !seen.contains(service);
+            seen.push(service.clone());
+            first
+        });
+    }
+
+    // 修正从旧版本或手动编辑的文件中读取到的无效值
+    fn sanitize(&mut self) {
+        self.normalize();
+        if services::find(&self.default_service).is_none() {
+            self.default_service = Self::default().default_service;
+        }
+        self.window_modes.retain(|service, _| services::find(service).is_some());
+        self.preload_services.retain(|service| services::find(service).is_some());
+        if crate::logging::validate_filter(&self.log_filter).is_err() {
+            self.log_filter = Self::default().log_filter;
+        }
+    }
+
+    // 列出与另一份设置不同的字段名
+    fn changed_fields(&self, other: &Self) -> Vec<String> {
+        let (Value::Object(a), Value::Object(b)) = (json!(self), json!(other)) else {
+            return Vec::new();
+        };
+
+        a.iter().filter(|(key, value)| b.get(*key) != Some(value)).map(|(key, _)| key.clone()).collect()
+    }
+}
+
+// 迁移旧版本的设置文件，在启动时调用
+pub fn migrate<R: Runtime>(app_handle: &AppHandle<R>) -> Result<(), String> {
+    let store = app_handle.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    let version = store.get("version").and_then(|v| v.as_u64()).unwrap_or(1);
+
+    if version > CURRENT_VERSION {
+        // 由更新版本的应用写入，保持原样，读取时忽略无法识别的字段
+        return Ok(());
+    }
+
+    if version < 2 {
+        // 版本 1：每项设置是设置文件中的一个独立键
+        let mut settings = Map::new();
+        for key in ["default_service", "restore_last_url", "window_modes", "compact_hide_sidebar", "control_token"] {
+            if let Some(value) = store.get(key) {
+                settings.insert(key.to_string(), value);
+                store.delete(key);
+            }
+        }
+        store.set("settings", Value::Object(settings));
+    }
+
+    if version < CURRENT_VERSION {
+        store.set("version", CURRENT_VERSION);
+        store.save().map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+// 读取设置，缺失或无效的字段使用默认值
+pub fn get<R: Runtime>(app_handle: &AppHandle<R>) -> Settings {
+    let mut settings: Settings = app_handle
+        .store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get("settings"))
+        .and_then(|settings| serde_json::from_value(settings).ok())
+        .unwrap_or_default();
+    settings.sanitize();
+
+    settings
+}
+
+// 修改设置：验证并保存后立即应用到窗口、托盘、窗口标题和主题，并通知设置页面
+pub fn update<R: Runtime, F: FnOnce(&mut Settings)>(app_handle: &AppHandle<R>, f: F) -> Result<Settings, String> {
+    try_update(app_handle, |settings| {
+        f(settings);
+        Ok(())
+    })
+}
+
+// 修改设置，修改函数返回错误时不保存
+fn try_update<R: Runtime, F: FnOnce(&mut Settings) -> Result<(), String>>(app_handle: &AppHandle<R>, f: F) -> Result<Settings, String> {
+    let lock = app_handle.state::<SettingsLock>();
+    let guard = lock.0.lock().map_err(|e| e.to_string())?;
+
+    let old = get(app_handle);
+    let mut new = old.clone();
+    f(&mut new)?;
+    new.normalize();
+    new.validate()?;
+
+    let changed = new.changed_fields(&old);
+    if changed.is_empty() {
+        return Ok(new);
+    }
+
+    window_modes::prepare_settings(app_handle, &old, &new)?;
+    if new.launch_at_login != old.launch_at_login {
+        let autostart = app_handle.state::<Box<dyn crate::autostart::Autostart>>();
+        crate::autostart::set_enabled(autostart.as_ref(), new.launch_at_login)?;
+    }
+
+    let store = app_handle.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set("settings", serde_json::to_value(&new).map_err(|e| e.to_string())?);
+    store.set("version", CURRENT_VERSION);
+    store.save().map_err(|e| e.to_string())?;
+    drop(guard);
+
+    // 设置已经保存，应用到界面时出错不影响结果
+    crate::logging::log_err!(window_modes::apply_settings(app_handle, &old, &new), "应用窗口模式失败");
+    crate::logging::log_err!(crate::tray::refresh(app_handle), "更新托盘失败");
+    if new.language != old.language {
+        crate::i18n::set_active(app_handle);
+        crate::logging::log_err!(crate::i18n::apply_titles(app_handle), "更新窗口标题失败");
+    }
+    if new.log_filter != old.log_filter {
+        crate::logging::log_err!(crate::logging::set_filter(&new.log_filter), "修改日志级别失败");
+    }
+    if new.theme != old.theme {
+        crate::logging::log_err!(crate::theme::apply_all(app_handle), "应用主题失败");
+    }
+    crate::logging::log_err!(app_handle.emit(SETTINGS_CHANGED_EVENT, SettingsChanged { changed }), "发送设置变化事件失败");
+
+    Ok(new)
+}
+
+// 用 JSON Merge Patch（RFC 7386）修改设置，只需提供要修改的字段
+pub fn patch<R: Runtime>(app_handle: &AppHandle<R>, patch: Value) -> Result<Settings, String> {
+    try_update(app_handle, |settings| {
+        *settings = apply_patch(settings, patch)?;
+        Ok(())
+    })
+}
+
+fn apply_patch(settings: &Settings, patch: Value) -> Result<Settings, String> {
+    let mut merged = serde_json::to_value(settings).map_err(|e| e.to_string())?;
+    merge_patch(&mut merged, patch);
+
+    serde_json::from_value(merged).map_err(|e| crate::i18n::tr("error-invalid-settings", &[("error", &e.to_string())]))
+}
+
+fn merge_patch(target: &mut Value, patch: Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch;
+        return;
+    };
+    if !target.is_object() {
+        *target = json!({});
+    }
+
+    let target = target.as_object_mut().expect("target is an object");
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(&key);
+        } else {
+            merge_patch(target.entry(key).or_insert(Value::Null), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::mock_app;
+
+    #[test]
+    fn migrates_version_1_keys_into_one_object() {
+        let app = mock_app();
+        let store = app.store(SETTINGS_STORE).unwrap();
+        store.set("default_service", "poe");
+        store.set("restore_last_url", false);
+        store.set("window_modes", json!({ "poe": { "pinned": true } }));
+        store.set("unrelated", 1);
+
+        migrate(app.handle()).unwrap();
+
+        assert_eq!(store.get("version"), Some(json!(CURRENT_VERSION)));
+        assert_eq!(store.get("default_service"), None);
+        assert_eq!(store.get("unrelated"), Some(json!(1)));
+        let settings = get(app.handle());
+        assert_eq!(settings.default_service, "poe");
+        assert!(!settings.restore_last_url);
+        assert!(settings.window_modes("poe").pinned);
+        assert!(settings.compact_hide_sidebar);
+    }
+
+    #[test]
+    fn leaves_settings_from_newer_versions_alone() {
+        let app = mock_app();
+        let store = app.store(SETTINGS_STORE).unwrap();
+        store.set("version", CURRENT_VERSION + 1);
+        store.set("settings", json!({ "default_service": "poe", "added_later": true }));
+
+        migrate(app.handle()).unwrap();
+
+        assert_eq!(store.get("version"), Some(json!(CURRENT_VERSION + 1)));
+        assert_eq!(store.get("settings").unwrap()["added_later"], json!(true));
+        assert_eq!(get(app.handle()).default_service, "poe");
+    }
+
+    #[test]
+    fn merge_patch_follows_rfc_7386() {
+        let mut target = json!({ "a": "b", "c": { "d": "e", "f": "g" }, "list": [1, 2] });
+        merge_patch(&mut target, json!({ "a": "z", "c": { "f": null, "h": "i" }, "list": [3] }));
+        assert_eq!(target, json!({ "a": "z", "c": { "d": "e", "h": "i" }, "list": [3] }));
+
+        // null 删除字段，非对象的补丁替换整个值
+        let mut target = json!({ "a": { "b": "c" } });
+        merge_patch(&mut target, json!({ "a": null, "x": { "y": null } }));
+        assert_eq!(target, json!({ "x": {} }));
+        merge_patch(&mut target, json!(["replaced"]));
+        assert_eq!(target, json!(["replaced"]));
+
+        let mut target = json!("scalar");
+        merge_patch(&mut target, json!({ "a": 1 }));
+        assert_eq!(target, json!({ "a": 1 }));
+    }
+
+    #[test]
+    fn patch_resets_removed_fields_to_defaults() {
+        let mut settings = Settings {
+            log_filter: "debug".to_string(),
+            ..Settings::default()
+        };
+        settings.window_modes.insert("poe".to_string(), WindowModes { pinned: true, ..WindowModes::default() });
+
+        let patched = apply_patch(&settings, json!({ "log_filter": null, "window_modes": { "poe": { "compact": true } } })).unwrap();
+        assert_eq!(patched.log_filter, "info");
+        assert_eq!(patched.window_modes("poe"), WindowModes { pinned: true, compact: true, ..WindowModes::default() });
+    }
+
+    #[test]
+    fn rejects_invalid_patches_without_saving() {
+        let app = mock_app();
+        let before = get(app.handle());
+
+        for value in [
+            json!({ "restore_last_url": "yes" }),
+            json!({ "theme": "blue" }),
+            json!({ "default_service": "chatgpt" }),
+            json!({ "window_modes": { "chatgpt": { "pinned": true } } }),
+            json!({ "control_token": "two words" }),
+            json!({ "log_filter": "info,app=loud" }),
+        ] {
+            assert!(patch(app.handle(), value.clone()).is_err(), "{value}");
+        }
+
+        assert_eq!(get(app.handle()), before);
+        assert_eq!(app.store(SETTINGS_STORE).unwrap().get("settings"), None);
+    }
+
+    #[test]
+    fn validate_accepts_defaults_and_rejects_bad_values() {
+        assert_eq!(Settings::default().validate(), Ok(()));
+
+        let unknown_preload = Settings {
+            preload_services: vec!["chatgpt".to_string()],
+            ..Settings::default()
+        };
+        assert!(unknown_preload.validate().is_err());
+    }
+
+    #[test]
+    fn normalize_drops_empty_tokens_default_modes_and_duplicates() {
+        let mut settings = Settings {
+            control_token: Some("  ".to_string()),
+            preload_services: vec!["poe".to_string(), "gemini".to_string(), "poe".to_string()],
+            ..Settings::default()
+        };
+        settings.window_modes.insert("gemini".to_string(), WindowModes::default());
+        settings.normalize();
+
+        assert_eq!(settings.control_token, None);
+        assert!(settings.window_modes.is_empty());
+        assert_eq!(settings.preload_services, ["poe", "gemini"]);
+
+        let mut settings = Settings {
+            control_token: Some(" token ".to_string()),
+            ..Settings::default()
+        };
+        settings.normalize();
+        assert_eq!(settings.control_token.as_deref(), Some("token"));
+    }
+
+    #[test]
+    fn sanitize_repairs_invalid_values_read_from_disk() {
+        let mut settings = Settings {
+            default_service: "chatgpt".to_string(),
+            preload_services: vec!["chatgpt".to_string(), "poe".to_string()],
+            log_filter: "info,app=loud".to_string(),
+            ..Settings::default()
+        };
+        settings.window_modes.insert("chatgpt".to_string(), WindowModes { pinned: true, ..WindowModes::default() });
+        settings.sanitize();
+
+        assert_eq!(settings.default_service, Settings::default().default_service);
+        assert!(settings.window_modes.is_empty());
+        assert_eq!(settings.preload_services, ["poe"]);
+        assert_eq!(settings.log_filter, "info");
+        assert_eq!(settings.validate(), Ok(()));
+    }
+
+    #[test]
+    fn changed_fields_lists_top_level_keys() {
+        let old = Settings::default();
+        assert!(old.changed_fields(&old).is_empty());
+
+        let mut new = old.clone();
+        new.theme = ThemeSetting::Dark;
+        new.window_modes.insert("poe".to_string(), WindowModes { compact: true, ..WindowModes::default() });
+        assert_eq!(new.changed_fields(&old), ["theme", "window_modes"]);
+    }
+
+    #[test]
+    fn concurrent_updates_keep_every_change() {
+        let app = mock_app();
+        let handle = app.handle().clone();
+
+        let updates: Vec<fn(&mut Settings)> = vec![
+            |settings| settings.restore_last_url = false,
+            |settings| settings.compact_hide_sidebar = false,
+            |settings| settings.start_hidden = true,
+            |settings| settings.theme = ThemeSetting::Dark,
+            |settings| settings.close_behavior = CloseBehavior::Quit,
+            |settings| settings.control_token = Some("token".to_string()),
+        ];
+        let threads: Vec<_> = updates
+            .into_iter()
+            .map(|f| {
+                let handle = handle.clone();
+                std::thread::spawn(move || update(&handle, f).unwrap())
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let settings = get(&handle);
+        assert!(!settings.restore_last_url);
+        assert!(!settings.compact_hide_sidebar);
+        assert!(settings.start_hidden);
+        assert_eq!(settings.theme, ThemeSetting::Dark);
+        assert_eq!(settings.close_behavior, CloseBehavior::Quit);
+        assert_eq!(settings.control_token.as_deref(), Some("token"));
+    }
+}
@@ -0,0 +1,32 @@
+use crate::session::SESSION_STORE;
+use crate::settings::SETTINGS_STORE;
+use crate::{logging, services, session, window_state};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+
+// 退出应用：先记录各窗口的状态和页面，并将设置和会话立即写入磁盘
+// （存储插件延迟写入，直接退出可能丢失最近的修改）
+pub fn quit(app_handle: &AppHandle) {
+    tracing::info!("正在退出");
+
+    for window in app_handle.webview_windows().values() {
+        // 记录退出时的可见性，下次启动时恢复
+        let visible = window.is_visible().unwrap_or(false);
+        logging::log_err!(window_state::save(&window.as_ref().window(), visible), "保存窗口状态失败");
+        if services::find(window.label()).is_some() {
+            logging::log_err!(session::record_window_url(window), "记录窗口 URL 失败");
+        }
+    }
+
+    for name in [SETTINGS_STORE, SESSION_STORE] {
+        logging::log_err!(flush(app_handle, name), "保存存储失败");
+    }
+
+    // 凭证直接以文件形式保存，没有需要在退出时锁定的凭证库
+    app_handle.exit(0);
+}
+
+fn flush(app_handle: &AppHandle, name: &str) -> Result<(), String> {
+    let store = app_handle.store(name).map_err(|e| e.to_string())?;
+    store.save().map_err(|e| e.to_string())
+}
@@ -0,0 +1,83 @@
+// 测试辅助：使用模拟运行时创建应用，设置、会话和归档写入临时目录
+use crate::app_state::StateStore;
+use crate::archive::Archive;
+use crate::autostart::{Autostart, LaunchCommand};
+use crate::{rpc, settings};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, Once};
+use tauri::test::{mock_builder, mock_context, noop_assets, MockRuntime};
+use tauri::{App, AppHandle, Manager};
+
+// 每个测试应用使用不同的标识符，数据目录互不影响
+static NEXT_APP_ID: AtomicUsize = AtomicUsize::new(1);
+
+// 创建测试使用的临时目录，每次调用返回新的目录
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "ai-assistant-test-{}-{}-{}",
+        std::process::id(),
+        NEXT_APP_ID.fetch_add(1, Ordering::Relaxed),
+        name
+    ));
+    std::fs::create_dir_all(&dir).expect("failed to create temp dir");
+
+    dir
+}
+
+// 不修改系统的开机启动实现，记录最近一次设置的启动命令
+#[derive(Default)]
+pub struct FakeAutostart {
+    command: Mutex<Option<LaunchCommand>>,
+}
+
+impl Autostart for FakeAutostart {
+    fn is_enabled(&self) -> bool {
+        self.command.lock().unwrap().is_some()
+    }
+
+    fn enable(&self, command: &LaunchCommand) -> Result<(), String> {
+        *self.command.lock().unwrap() = Some(command.clone());
+        Ok(())
+    }
+
+    fn disable(&self) -> Result<(), String> {
+        *self.command.lock().unwrap() = None;
+        Ok(())
+    }
+}
+
+// 创建模拟应用，注册与正式应用相同的插件和状态，开机启动使用 FakeAutostart
+pub fn mock_app() -> App<MockRuntime> {
+    // Linux 上的应用数据目录由 XDG_DATA_HOME 决定，测试数据不写入用户目录
+    static DATA_HOME: Once = Once::new();
+    DATA_HOME.call_once(|| std::env::set_var("XDG_DATA_HOME", temp_dir("data")));
+
+    let mut context = mock_context(noop_assets());
+    context.config_mut().identifier = format!(
+        "com.lif.ai.assistant.test-{}-{}",
+        std::process::id(),
+        NEXT_APP_ID.fetch_add(1, Ordering::Relaxed)
+    );
+
+    let app = mock_builder()
+        .plugin(tauri_plugin_store::Builder::default().build())
+        .manage(StateStore::default())
+        .manage(settings::SettingsLock::default())
+        .manage(rpc::Subscribers::default())
+        .manage(Box::new(FakeAutostart::default()) as Box<dyn Autostart>)
+        .build(context)
+        .expect("failed to build mock app");
+
+    let data_dir = app.path().app_data_dir().expect("failed to resolve app data dir");
+    std::fs::create_dir_all(&data_dir).expect("failed to create app data dir");
+    app.manage(Archive::open(&data_dir.join("history.sqlite3")).expect("failed to open archive"));
+
+    app
+}
+
+// 设置控制令牌
+pub fn set_control_token(app_handle: &AppHandle<MockRuntime>, token: &str) {
+    settings::update(app_handle, |settings| settings.control_token = Some(token.to_string()))
+        .expect("failed to set control token");
+}
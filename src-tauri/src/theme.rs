@@ -0,0 +1,45 @@
+use crate::settings::{self, ThemeSetting};
+use crate::{page_style, services};
+use tauri::{AppHandle, Manager, Runtime, Theme, WebviewWindow};
+
+// 深色模式注入的样式 ID
+const DARK_STYLE_ID: &str = "ai-assistant-dark";
+
+// 将主题设置应用到窗口：设置原生窗口主题，并为深色支持不完善的服务注入样式
+pub fn apply<R: Runtime>(window: &WebviewWindow<R>) -> Result<(), String> {
+    let theme = match settings::get(window.app_handle()).theme {
+        ThemeSetting::System => None,
+        ThemeSetting::Light => Some(Theme::Light),
+        ThemeSetting::Dark => Some(Theme::Dark),
+    };
+    window.set_theme(theme).map_err(|e| e.to_string())?;
+
+    apply_page_style(window)
+}
+
+// 将主题设置应用到所有窗口
+pub fn apply_all<R: Runtime>(app_handle: &AppHandle<R>) -> Result<(), String> {
+    for window in app_handle.webview_windows().values() {
+        apply(window)?;
+    }
+
+    Ok(())
+}
+
+// 根据窗口当前的主题注入或移除服务的深色样式；页面重新加载或系统主题变化后调用
+pub fn apply_page_style<R: Runtime>(window: &WebviewWindow<R>) -> Result<(), String> {
+    let Some(service) = services::find(window.label()) else {
+        return Ok(());
+    };
+    if service.dark_css.is_empty() {
+        return Ok(());
+    }
+
+    let dark = match settings::get(window.app_handle()).theme {
+        ThemeSetting::System => window.theme().map_err(|e| e.to_string())? == Theme::Dark,
+        ThemeSetting::Light => false,
+        ThemeSetting::Dark => true,
+    };
+
+    page_style::apply(window, DARK_STYLE_ID, dark.then_some(service.dark_css))
+}
@@ -0,0 +1,139 @@
+use crate::tray_policy::ClickAction;
+use crate::{i18n, services, settings, window_modes};
+use tauri::image::Image;
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Manager, Runtime};
+
+// 系统托盘 ID
+pub const TRAY_ID: &str = "main";
+
+// 创建托盘菜单，勾选当前活动的服务并标记已打开的服务
+pub fn build_menu<R: Runtime>(app_handle: &AppHandle<R>, active: &str) -> tauri::Result<Menu<R>> {
+    let quit_item = MenuItem::with_id(app_handle, "quit", i18n::t(app_handle, "tray-quit"), true, None::<&str>)?;
+    let show_item = MenuItem::with_id(app_handle, "show", i18n::t(app_handle, "tray-show"), true, None::<&str>)?;
+    let hide_item = MenuItem::with_id(app_handle, "hide", i18n::t(app_handle, "tray-hide"), true, None::<&str>)?;
+    let settings_item =
+        MenuItem::with_id(app_handle, "settings", i18n::t(app_handle, "tray-settings"), true, None::<&str>)?;
+
+    let menu = Menu::with_items(app_handle, &[&show_item, &hide_item])?;
+
+    // 服务切换菜单项
+    menu.append(&PredefinedMenuItem::separator(app_handle)?)?;
+    for service in services::SERVICES {
+        let open = app_handle
+            .get_webview_window(service.id)
+            .is_some_and(|window| window.is_visible().unwrap_or(false));
+        let id = if open { "tray-switch-to-open" } else { "tray-switch-to" };
+        let text = i18n::t_args(app_handle, id, &[("service", service.name)]);
+
+        let item = CheckMenuItem::with_id(app_handle, service.id, text, true, service.id == active, None::<&str>)?;
+        menu.append(&item)?;
+    }
+
+    // 每个服务的窗口模式子菜单，勾选状态反映当前设置
+    menu.append(&PredefinedMenuItem::separator(app_handle)?)?;
+    for service in services::SERVICES {
+        let modes = window_modes::get(app_handle, service.id);
+        let pin_item = CheckMenuItem::with_id(
+            app_handle,
+            format!("pin:{}", service.id),
+            i18n::t(app_handle, "tray-pin"),
+            true,
+            modes.pinned,
+            None::<&str>,
+        )?;
+        let compact_item = CheckMenuItem::with_id(
+            app_handle,
+            format!("compact:{}", service.id),
+            i18n::t(app_handle, "tray-compact"),
+            true,
+            modes.compact,
+            None::<&str>,
+        )?;
+        let popover_item = CheckMenuItem::with_id(
+            app_handle,
+            format!("popover:{}", service.id),
+            i18n::t(app_handle, "tray-popover"),
+            true,
+            modes.popover,
+            None::<&str>,
+        )?;
+        let auto_hide_item = CheckMenuItem::with_id(
+            app_handle,
+            format!("auto_hide:{}", service.id),
+            i18n::t(app_handle, "tray-auto-hide"),
+            true,
+            modes.auto_hide,
+            None::<&str>,
+        )?;
+        let submenu = Submenu::with_items(
+            app_handle,
+            service.name,
+            true,
+            &[&pin_item, &compact_item, &popover_item, &auto_hide_item],
+        )?;
+        menu.append(&submenu)?;
+    }
+    menu.append(&PredefinedMenuItem::separator(app_handle)?)?;
+
+    menu.append_items(&[&settings_item, &quit_item])?;
+
+    Ok(menu)
+}
+
+// 重新创建托盘菜单，并将托盘图标和提示切换为当前活动的服务
+pub fn refresh<R: Runtime>(app_handle: &AppHandle<R>) -> tauri::Result<()> {
+    let Some(tray) = app_handle.tray_by_id(TRAY_ID) else {
+        return Ok(());
+    };
+
+    let active = crate::last_active_service(app_handle);
+    tray.set_menu(Some(build_menu(app_handle, &active)?))?;
+
+    // 左键点击设置为显示菜单时由系统显示菜单
+    let left_click = settings::get(app_handle).tray_actions.left_click;
+    tray.set_show_menu_on_left_click(left_click == ClickAction::Menu)?;
+
+    if let Some(service) = services::find(&active) {
+        // 有未查看的回答时显示带红点的图标
+        let has_pending = !crate::pending_responses(app_handle).is_empty();
+        let icon = if has_pending {
+            badge_icon(service.icon)
+        } else {
+            None
+        };
+        let icon = match icon.map_or_else(|| Image::from_bytes(service.icon), Ok) {
+            Ok(icon) => Some(icon),
+            // 服务图标无法解码时使用应用默认图标，不影响托盘的其他功能
+            Err(e) => {
+                tracing::error!(service = service.id, error = %e, "无法解码服务图标");
+                app_handle.default_window_icon().cloned()
+            }
+        };
+        tray.set_icon(icon)?;
+
+        let id = if has_pending { "tray-tooltip-pending" } else { "tray-tooltip" };
+        let tooltip = i18n::t_args(app_handle, id, &[("service", service.name)]);
+        tray.set_tooltip(Some(tooltip))?;
+    }
+
+    Ok(())
+}
+
+// 在图标右上角绘制红点，表示有未查看的回答
+fn badge_icon(icon: &[u8]) -> Option<Image<'static>> {
+    let mut pixels = image::load_from_memory(icon).ok()?.to_rgba8();
+    let (width, height) = pixels.dimensions();
+
+    let radius = width.min(height) as f32 / 4.0;
+    let (center_x, center_y) = (width as f32 - radius, radius);
+    for (x, y, pixel) in pixels.enumerate_pixels_mut() {
+        let dx = x as f32 + 0.5 - center_x;
+        let dy = y as f32 + 0.5 - center_y;
+        if dx * dx + dy * dy <= radius * radius {
+            *pixel = image::Rgba([231, 76, 60, 255]);
+        }
+    }
+
+    Some(Image::new_owned(pixels.into_raw(), width, height))
+}
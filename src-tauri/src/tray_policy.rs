@@ -0,0 +1,279 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::tray::MouseButton;
+
+// 两次左键点击的间隔小于该值时视为双击
+pub const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(300);
+
+// 弹出窗口因点击托盘图标失去焦点而隐藏后，这段时间内的点击视为关闭弹出窗口
+pub const REOPEN_GUARD: Duration = Duration::from_millis(300);
+
+// 定义托盘图标的点击方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Click {
+    Left,
+    Double,
+    Middle,
+    Right,
+}
+
+// 定义点击托盘图标时执行的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClickAction {
+    // 没有窗口可见时显示服务窗口，窗口在后台时隐藏，窗口在前台时保持在前台
+    Toggle,
+    // 显示最近使用的服务窗口
+    ShowMru,
+    // 显示最近使用的服务窗口并聚焦输入框
+    QuickAsk,
+    // 隐藏所有窗口
+    Hide,
+    // 显示托盘菜单（由系统处理）
+    Menu,
+    // 不执行任何操作
+    Nothing,
+}
+
+// 定义各点击方式对应的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrayActions {
+    pub left_click: ClickAction,
+    pub double_click: ClickAction,
+    pub middle_click: ClickAction,
+    // 大多数平台在右键点击时总会显示托盘菜单
+    pub right_click: ClickAction,
+}
+
+impl Default for TrayActions {
+    fn default() -> Self {
+        Self {
+            left_click: ClickAction::Toggle,
+            double_click: ClickAction::Hide,
+            middle_click: ClickAction::Nothing,
+            right_click: ClickAction::Menu,
+        }
+    }
+}
+
+impl TrayActions {
+    // 获取点击方式对应的操作
+    pub fn action(&self, click: Click) -> ClickAction {
+        match click {
+            Click::Left => self.left_click,
+            Click::Double => self.double_click,
+            Click::Middle => self.middle_click,
+            Click::Right => self.right_click,
+        }
+    }
+}
+
+// 定义决策时的窗口状态
+#[derive(Debug, Clone)]
+pub struct WindowSnapshot {
+    pub label: String,
+    pub visible: bool,
+    pub focused: bool,
+    // 窗口在点击前刚因失去焦点而自动隐藏（弹出模式或自动隐藏模式）
+    pub auto_hidden: bool,
+}
+
+// 定义决策时的应用状态
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    // 按显示优先级排列的窗口
+    pub windows: Vec<WindowSnapshot>,
+    // 有未查看回答的服务（最新的在前）
+    pub pending_responses: Vec<String>,
+    // 最近使用的服务
+    pub last_active: String,
+}
+
+// 定义决策的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrayAction {
+    // 切换到服务窗口
+    Show(String),
+    // 切换到服务窗口并聚焦输入框
+    QuickAsk(String),
+    // 将窗口置于前台
+    Focus(String),
+    // 隐藏窗口
+    Hide(String),
+}
+
+// 根据按键和与上次左键点击的间隔判断点击方式
+pub fn classify(button: MouseButton, interval: Option<Duration>) -> Click {
+    match button {
+        MouseButton::Left if interval.is_some_and(|interval| interval < DOUBLE_CLICK_INTERVAL) => Click::Double,
+        MouseButton::Left => Click::Left,
+        MouseButton::Middle => Click::Middle,
+        MouseButton::Right => Click::Right,
+    }
+}
+
+// 决定点击托盘图标后要执行的操作，不读取也不修改任何外部状态
+pub fn decide(action: ClickAction, snapshot: &Snapshot) -> Vec<TrayAction> {
+    // 点击托盘图标使弹出窗口失去焦点而隐藏时，仍视为可见，避免再次弹出
+    let visible: Vec<&WindowSnapshot> = snapshot.windows.iter().filter(|w| w.visible || w.auto_hidden).collect();
+
+    match action {
+        ClickAction::Toggle if visible.is_empty() => {
+            // 没有窗口可见时，优先显示有未查看回答的服务窗口，其次是最近使用的服务窗口
+            let service = snapshot.pending_responses.first().unwrap_or(&snapshot.last_active);
+            vec![TrayAction::Show(service.clone())]
+        }
+        ClickAction::Toggle if !visible.iter().any(|w| w.focused) => hide_all(&visible),
+        // 有窗口在前台时，将第一个可见窗口置于前台
+        ClickAction::Toggle => vec![TrayAction::Focus(visible[0].label.clone())],
+        ClickAction::ShowMru => vec![TrayAction::Show(snapshot.last_active.clone())],
+        ClickAction::QuickAsk => vec![TrayAction::QuickAsk(snapshot.last_active.clone())],
+        ClickAction::Hide => hide_all(&visible),
+        ClickAction::Menu | ClickAction::Nothing => Vec::new(),
+    }
+}
+
+fn hide_all(visible: &[&WindowSnapshot]) -> Vec<TrayAction> {
+    visible.iter().map(|w| TrayAction::Hide(w.label.clone())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(label: &str, visible: bool, focused: bool) -> WindowSnapshot {
+        WindowSnapshot {
+            label: label.to_string(),
+            visible,
+            focused,
+            auto_hidden: false,
+        }
+    }
+
+    fn snapshot(windows: Vec<WindowSnapshot>) -> Snapshot {
+        Snapshot {
+            windows,
+            pending_responses: Vec::new(),
+            last_active: "gemini".to_string(),
+        }
+    }
+
+    #[test]
+    fn first_left_click_is_a_single_click() {
+        assert_eq!(classify(MouseButton::Left, None), Click::Left);
+    }
+
+    #[test]
+    fn left_click_within_the_interval_is_a_double_click() {
+        assert_eq!(classify(MouseButton::Left, Some(Duration::from_millis(120))), Click::Double);
+    }
+
+    #[test]
+    fn left_click_outside_the_interval_is_a_single_click() {
+        assert_eq!(classify(MouseButton::Left, Some(DOUBLE_CLICK_INTERVAL)), Click::Left);
+        assert_eq!(classify(MouseButton::Left, Some(Duration::from_secs(2))), Click::Left);
+    }
+
+    #[test]
+    fn other_buttons_ignore_the_interval() {
+        let interval = Some(Duration::from_millis(120));
+        assert_eq!(classify(MouseButton::Middle, interval), Click::Middle);
+        assert_eq!(classify(MouseButton::Right, interval), Click::Right);
+    }
+
+    #[test]
+    fn double_click_uses_its_own_action() {
+        let actions = TrayActions::default();
+        assert_eq!(actions.action(classify(MouseButton::Left, None)), ClickAction::Toggle);
+        assert_eq!(actions.action(classify(MouseButton::Left, Some(Duration::from_millis(120)))), ClickAction::Hide);
+    }
+
+    #[test]
+    fn toggle_shows_the_last_service_when_nothing_is_visible() {
+        let snapshot = snapshot(vec![window("gemini", false, false), window("poe", false, false)]);
+
+        assert_eq!(decide(ClickAction::Toggle, &snapshot), vec![TrayAction::Show("gemini".to_string())]);
+    }
+
+    #[test]
+    fn toggle_shows_the_last_service_when_its_window_is_missing() {
+        // 最近使用的服务窗口尚未创建时也显示它，由调用方创建窗口
+        let mut snapshot = snapshot(Vec::new());
+        snapshot.last_active = "poe".to_string();
+
+        assert_eq!(decide(ClickAction::Toggle, &snapshot), vec![TrayAction::Show("poe".to_string())]);
+    }
+
+    #[test]
+    fn toggle_prefers_services_with_pending_responses() {
+        let mut snapshot = snapshot(vec![window("gemini", false, false), window("poe", false, false)]);
+        snapshot.pending_responses = vec!["poe".to_string(), "gemini".to_string()];
+
+        assert_eq!(decide(ClickAction::Toggle, &snapshot), vec![TrayAction::Show("poe".to_string())]);
+    }
+
+    #[test]
+    fn toggle_hides_visible_windows_in_the_background() {
+        // 普通模式下窗口失去焦点后仍然可见，点击托盘图标隐藏窗口
+        let snapshot = snapshot(vec![window("gemini", true, false), window("poe", false, false), window("settings", true, false)]);
+
+        assert_eq!(
+            decide(ClickAction::Toggle, &snapshot),
+            vec![TrayAction::Hide("gemini".to_string()), TrayAction::Hide("settings".to_string())]
+        );
+    }
+
+    #[test]
+    fn toggle_focuses_the_first_visible_window_when_one_is_focused() {
+        let snapshot = snapshot(vec![window("gemini", false, false), window("poe", true, false), window("settings", true, true)]);
+
+        assert_eq!(decide(ClickAction::Toggle, &snapshot), vec![TrayAction::Focus("poe".to_string())]);
+    }
+
+    #[test]
+    fn toggle_does_not_reopen_a_popover_hidden_by_the_click() {
+        // 弹出模式下点击托盘图标使窗口失去焦点并自动隐藏，此次点击应关闭弹出窗口
+        let mut popover = window("gemini", false, false);
+        popover.auto_hidden = true;
+        let snapshot = snapshot(vec![popover, window("poe", false, false)]);
+
+        assert_eq!(decide(ClickAction::Toggle, &snapshot), vec![TrayAction::Hide("gemini".to_string())]);
+    }
+
+    #[test]
+    fn toggle_reopens_a_popover_hidden_earlier() {
+        // 弹出窗口在更早之前自动隐藏时，点击托盘图标重新显示
+        let snapshot = snapshot(vec![window("gemini", false, false), window("poe", false, false)]);
+
+        assert_eq!(decide(ClickAction::Toggle, &snapshot), vec![TrayAction::Show("gemini".to_string())]);
+    }
+
+    #[test]
+    fn show_and_quick_ask_use_the_last_service() {
+        let mut snapshot = snapshot(vec![window("gemini", true, true)]);
+        snapshot.last_active = "poe".to_string();
+        snapshot.pending_responses = vec!["gemini".to_string()];
+
+        assert_eq!(decide(ClickAction::ShowMru, &snapshot), vec![TrayAction::Show("poe".to_string())]);
+        assert_eq!(decide(ClickAction::QuickAsk, &snapshot), vec![TrayAction::QuickAsk("poe".to_string())]);
+    }
+
+    #[test]
+    fn hide_hides_every_visible_window() {
+        let snapshot = snapshot(vec![window("gemini", true, true), window("poe", false, false), window("settings", true, false)]);
+
+        assert_eq!(
+            decide(ClickAction::Hide, &snapshot),
+            vec![TrayAction::Hide("gemini".to_string()), TrayAction::Hide("settings".to_string())]
+        );
+    }
+
+    #[test]
+    fn menu_and_nothing_do_nothing() {
+        let snapshot = snapshot(vec![window("gemini", true, false)]);
+
+        assert!(decide(ClickAction::Menu, &snapshot).is_empty());
+        assert!(decide(ClickAction::Nothing, &snapshot).is_empty());
+    }
+}
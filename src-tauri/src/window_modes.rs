@@ -0,0 +1,267 @@
+use crate::settings::{self, Settings};
+use crate::{page_style, services, window_state};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, LogicalSize, Manager, PhysicalPosition, PhysicalSize, Rect, Runtime, WebviewWindow};
+
+// 紧凑模式的窗口大小
+const COMPACT_WIDTH: f64 = 420.0;
+const COMPACT_HEIGHT: f64 = 720.0;
+
+// 弹出模式的窗口大小
+const POPOVER_WIDTH: f64 = 420.0;
+const POPOVER_HEIGHT: f64 = 640.0;
+
+// 紧凑模式注入的样式 ID
+const COMPACT_STYLE_ID: &str = "ai-assistant-compact";
+
+// 定义窗口模式结构体
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowModes {
+    // 窗口置顶
+    pub pinned: bool,
+    // 紧凑模式
+    pub compact: bool,
+    // 弹出模式：在托盘图标旁显示，失去焦点时自动隐藏
+    pub popover: bool,
+    // 失去焦点时自动隐藏
+    pub auto_hide: bool,
+}
+
+impl WindowModes {
+    // 是否使用特殊的窗口大小，此时不应记录窗口的位置和大小
+    pub fn keeps_geometry(&self) -> bool {
+        self.compact || self.popover
+    }
+}
+
+// 读取服务的窗口模式
+pub fn get<R: Runtime>(app_handle: &AppHandle<R>, service: &str) -> WindowModes {
+    settings::get(app_handle).window_modes(service)
+}
+
+// 紧凑模式下是否隐藏服务的侧边栏
+pub fn compact_hides_sidebar<R: Runtime>(app_handle: &AppHandle<R>) -> bool {
+    settings::get(app_handle).compact_hide_sidebar
+}
+
+// 设置窗口置顶
+pub fn set_pinned<R: Runtime>(app_handle: &AppHandle<R>, service: &str, pinned: bool) -> Result<(), String> {
+    settings::update(app_handle, |settings| {
+        settings.window_modes.entry(service.to_string()).or_default().pinned = pinned;
+    })
+    .map(|_| ())
+}
+
+// 设置紧凑模式
+pub fn set_compact<R: Runtime>(app_handle: &AppHandle<R>, service: &str, compact: bool) -> Result<(), String> {
+    settings::update(app_handle, |settings| {
+        settings.window_modes.entry(service.to_string()).or_default().compact = compact;
+    })
+    .map(|_| ())
+}
+
+// 设置弹出模式
+pub fn set_popover<R: Runtime>(app_handle: &AppHandle<R>, service: &str, popover: bool) -> Result<(), String> {
+    settings::update(app_handle, |settings| {
+        settings.window_modes.entry(service.to_string()).or_default().popover = popover;
+    })
+    .map(|_| ())
+}
+
+// 设置失去焦点时自动隐藏
+pub fn set_auto_hide<R: Runtime>(app_handle: &AppHandle<R>, service: &str, auto_hide: bool) -> Result<(), String> {
+    settings::update(app_handle, |settings| {
+        settings.window_modes.entry(service.to_string()).or_default().auto_hide = auto_hide;
+    })
+    .map(|_| ())
+}
+
+// 保存设置前调用：进入紧凑模式或弹出模式前记录正常状态下的位置和大小
+pub fn prepare_settings<R: Runtime>(app_handle: &AppHandle<R>, old: &Settings, new: &Settings) -> Result<(), String> {
+    for service in services::SERVICES {
+        let (old_modes, new_modes) = (old.window_modes(service.id), new.window_modes(service.id));
+        if !old_modes.keeps_geometry() && new_modes.keeps_geometry() {
+            if let Some(window) = app_handle.get_webview_window(service.id) {
+                window_state::save(&window.as_ref().window(), true)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// 保存设置后调用：将变化的窗口模式应用到已打开的窗口
+pub fn apply_settings<R: Runtime>(app_handle: &AppHandle<R>, old: &Settings, new: &Settings) -> Result<(), String> {
+    for service in services::SERVICES {
+        let Some(window) = app_handle.get_webview_window(service.id) else {
+            continue;
+        };
+        let (old_modes, new_modes) = (old.window_modes(service.id), new.window_modes(service.id));
+
+        if new_modes.pinned != old_modes.pinned {
+            window.set_always_on_top(new_modes.pinned).map_err(|e| e.to_string())?;
+        }
+        if new_modes.popover != old_modes.popover {
+            apply_popover(&window, new_modes)?;
+        } else if new_modes.compact != old_modes.compact && new_modes.popover {
+            // 弹出模式下紧凑模式只影响页面样式
+            apply_compact_style(&window, new_modes.compact)?;
+        } else if new_modes.compact != old_modes.compact {
+            apply_compact(&window, new_modes.compact)?;
+        } else if new_modes.compact && new.compact_hide_sidebar != old.compact_hide_sidebar {
+            apply_compact_style(&window, true)?;
+        }
+    }
+
+    Ok(())
+}
+
+// 在窗口创建时应用已保存的窗口模式
+pub fn apply<R: Runtime>(window: &WebviewWindow<R>) -> Result<(), String> {
+    let modes = get(window.app_handle(), window.label());
+
+    if modes.pinned {
+        window.set_always_on_top(true).map_err(|e| e.to_string())?;
+    }
+    if modes.popover {
+        apply_popover(window, modes)?;
+    } else if modes.compact {
+        apply_compact(window, true)?;
+    }
+
+    Ok(())
+}
+
+// 弹出模式下将窗口移到托盘图标旁，托盘图标位置未知时保持原位
+pub fn anchor_to_tray<R: Runtime>(window: &WebviewWindow<R>) -> Result<(), String> {
+    let Some(tray) = window.app_handle().tray_by_id(crate::tray::TRAY_ID) else {
+        return Ok(());
+    };
+    let Some(rect) = tray.rect().map_err(|e| e.to_string())? else {
+        return Ok(());
+    };
+
+    let (tray_position, _) = physical_rect(&rect, 1.0);
+    let Some(monitor) = window
+        .monitor_from_point(tray_position.x as f64, tray_position.y as f64)
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(());
+    };
+    let (tray_position, tray_size) = physical_rect(&rect, monitor.scale_factor());
+
+    let size = window.outer_size().map_err(|e| e.to_string())?;
+    let work_area = monitor.work_area();
+    let (x, y) = anchor((tray_position, tray_size), size, (work_area.position, work_area.size));
+
+    window.set_position(PhysicalPosition::new(x, y)).map_err(|e| e.to_string())
+}
+
+fn physical_rect(rect: &Rect, scale_factor: f64) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+    (rect.position.to_physical(scale_factor), rect.size.to_physical(scale_factor))
+}
+
+// 计算弹出窗口的位置：水平居中于托盘图标，托盘在屏幕下半部分时显示在图标上方，否则显示在下方，
+// 并限制在显示器的工作区内，避免被任务栏或菜单栏遮挡
+fn anchor(
+    (tray_position, tray_size): (PhysicalPosition<i32>, PhysicalSize<u32>),
+    window: PhysicalSize<u32>,
+    (area_position, area_size): (PhysicalPosition<i32>, PhysicalSize<u32>),
+) -> (i32, i32) {
+    let x = tray_position.x + tray_size.width as i32 / 2 - window.width as i32 / 2;
+    let tray_center_y = tray_position.y + tray_size.height as i32 / 2;
+    let y = if tray_center_y > area_position.y + area_size.height as i32 / 2 {
+        tray_position.y - window.height as i32
+    } else {
+        tray_position.y + tray_size.height as i32
+    };
+
+    let max_x = area_position.x + area_size.width.saturating_sub(window.width) as i32;
+    let max_y = area_position.y + area_size.height.saturating_sub(window.height) as i32;
+
+    (x.clamp(area_position.x, max_x), y.clamp(area_position.y, max_y))
+}
+
+// 页面重新加载后恢复紧凑模式的样式
+pub fn apply_page_style<R: Runtime>(window: &WebviewWindow<R>) -> Result<(), String> {
+    if get(window.app_handle(), window.label()).compact {
+        apply_compact_style(window, true)?;
+    }
+
+    Ok(())
+}
+
+fn apply_popover<R: Runtime>(window: &WebviewWindow<R>, modes: WindowModes) -> Result<(), String> {
+    window.set_skip_taskbar(modes.popover).map_err(|e| e.to_string())?;
+    if modes.popover {
+        window.set_decorations(false).map_err(|e| e.to_string())?;
+        window.set_size(LogicalSize::new(POPOVER_WIDTH, POPOVER_HEIGHT)).map_err(|e| e.to_string())?;
+        return apply_compact_style(window, modes.compact);
+    }
+
+    // 退出弹出模式后恢复紧凑模式或正常状态
+    apply_compact(window, modes.compact)
+}
+
+fn apply_compact<R: Runtime>(window: &WebviewWindow<R>, compact: bool) -> Result<(), String> {
+    window.set_decorations(!compact).map_err(|e| e.to_string())?;
+
+    if compact {
+        window.set_size(LogicalSize::new(COMPACT_WIDTH, COMPACT_HEIGHT)).map_err(|e| e.to_string())?;
+    } else if let Some(state) = window_state::load(window.app_handle(), window.label()) {
+        // 退出紧凑模式时恢复正常状态下的位置和大小
+        window.set_size(PhysicalSize::new(state.width, state.height)).map_err(|e| e.to_string())?;
+        window.set_position(PhysicalPosition::new(state.x, state.y)).map_err(|e| e.to_string())?;
+    }
+
+    apply_compact_style(window, compact)
+}
+
+fn apply_compact_style<R: Runtime>(window: &WebviewWindow<R>, compact: bool) -> Result<(), String> {
+    let css = services::find(window.label())
+        .filter(|_| compact && compact_hides_sidebar(window.app_handle()))
+        .map(|service| service.compact_css);
+
+    page_style::apply(window, COMPACT_STYLE_ID, css)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: PhysicalSize<u32> = PhysicalSize::new(400, 600);
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+        (PhysicalPosition::new(x, y), PhysicalSize::new(width, height))
+    }
+
+    #[test]
+    fn opens_above_a_tray_at_the_bottom() {
+        // 任务栏在底部，工作区不包括任务栏
+        assert_eq!(anchor(rect(1800, 1045, 24, 24), WINDOW, rect(0, 0, 1920, 1040)), (1520, 440));
+        assert_eq!(anchor(rect(900, 1045, 24, 24), WINDOW, rect(0, 0, 1920, 1040)), (712, 440));
+    }
+
+    #[test]
+    fn opens_below_a_tray_at_the_top() {
+        // macOS 菜单栏在顶部
+        assert_eq!(anchor(rect(1500, 0, 22, 22), WINDOW, rect(0, 25, 1920, 1055)), (1311, 25));
+    }
+
+    #[test]
+    fn stays_beside_a_taskbar_on_the_left() {
+        assert_eq!(anchor(rect(12, 1000, 24, 24), WINDOW, rect(48, 0, 1872, 1080)), (48, 400));
+    }
+
+    #[test]
+    fn stays_beside_a_taskbar_on_the_right() {
+        assert_eq!(anchor(rect(1884, 1000, 24, 24), WINDOW, rect(0, 0, 1872, 1080)), (1472, 400));
+    }
+
+    #[test]
+    fn uses_the_work_area_of_a_secondary_monitor() {
+        assert_eq!(anchor(rect(3700, 1045, 24, 24), WINDOW, rect(1920, 0, 1920, 1040)), (3440, 440));
+        assert_eq!(anchor(rect(1950, 1045, 24, 24), WINDOW, rect(1920, 0, 1920, 1040)), (1920, 440));
+    }
+}
@@ -0,0 +1,226 @@
+use crate::session::SESSION_STORE;
+use crate::{logging, window_modes};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{Manager, Monitor, PhysicalPosition, PhysicalSize, Runtime, WebviewWindow, Window};
+use tauri_plugin_store::StoreExt;
+
+// 定义窗口状态结构体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowState {
+    // 未最大化时的位置和大小（物理像素）
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub maximized: bool,
+    // 窗口所在显示器的名称
+    pub monitor: Option<String>,
+    pub visible: bool,
+}
+
+// 读取窗口的已保存状态
+pub fn load<R: Runtime>(app_handle: &tauri::AppHandle<R>, label: &str) -> Option<WindowState> {
+    let store = app_handle.store(SESSION_STORE).ok()?;
+    let state = store.get("windows")?.get(label)?.clone();
+    serde_json::from_value(state).ok()
+}
+
+// 记录窗口当前的状态
+pub fn save<R: Runtime>(window: &Window<R>, visible: bool) -> Result<(), String> {
+    // 最小化时的位置没有意义（Windows 上会移动到 -32000）
+    if window.is_minimized().unwrap_or(false) {
+        return Ok(());
+    }
+
+    let app_handle = window.app_handle();
+    let maximized = window.is_maximized().map_err(|e| e.to_string())?;
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|m| m.name().cloned());
+
+    // 最大化、紧凑模式或弹出模式时保留之前的位置和大小，以便恢复
+    let keep_geometry = maximized || window_modes::get(app_handle, window.label()).keeps_geometry();
+    let previous = load(app_handle, window.label());
+    let state = match previous {
+        Some(previous) if keep_geometry => WindowState {
+            maximized,
+            monitor,
+            visible,
+            ..previous
+        },
+        _ => {
+            let position = window.outer_position().map_err(|e| e.to_string())?;
+            let size = window.inner_size().map_err(|e| e.to_string())?;
+            WindowState {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+                maximized,
+                monitor,
+                visible,
+            }
+        }
+    };
+
+    let store = app_handle.store(SESSION_STORE).map_err(|e| e.to_string())?;
+    let mut windows = store
+        .get("windows")
+        .filter(|v| v.is_object())
+        .unwrap_or_else(|| json!({}));
+    windows[window.label()] = serde_json::to_value(&state).map_err(|e| e.to_string())?;
+    store.set("windows", windows);
+
+    Ok(())
+}
+
+// 记录状态后隐藏窗口
+pub fn hide<R: Runtime>(window: &WebviewWindow<R>) -> Result<(), String> {
+    save(&window.as_ref().window(), false)?;
+    window.hide().map_err(|e| e.to_string())
+}
+
+// 在窗口创建时恢复已保存的状态，返回上次退出时窗口是否可见
+pub fn restore<R: Runtime>(window: &WebviewWindow<R>) -> Option<bool> {
+    let mut state = load(window.app_handle(), window.label())?;
+
+    let screens: Vec<Screen> = window.available_monitors().unwrap_or_default().iter().map(Screen::from).collect();
+    let primary = window.primary_monitor().ok().flatten().map(|m| Screen::from(&m));
+    if let Some(screen) = pick_monitor(&state, &screens, primary.as_ref()) {
+        clamp_to_monitor(&mut state, (screen.position, screen.size));
+    }
+
+    logging::log_err!(window.set_size(PhysicalSize::new(state.width, state.height)), "恢复窗口大小失败");
+    logging::log_err!(window.set_position(PhysicalPosition::new(state.x, state.y)), "恢复窗口位置失败");
+    if state.maximized {
+        logging::log_err!(window.maximize(), "恢复最大化失败");
+    }
+
+    Some(state.visible)
+}
+
+// 显示器的名称和范围（物理像素）
+#[derive(Debug, Clone, PartialEq)]
+struct Screen {
+    name: Option<String>,
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+}
+
+impl From<&Monitor> for Screen {
+    fn from(monitor: &Monitor) -> Self {
+        Screen {
+            name: monitor.name().cloned(),
+            position: *monitor.position(),
+            size: *monitor.size(),
+        }
+    }
+}
+
+// 选择窗口应所在的显示器：优先选择包含窗口标题栏的显示器，其次是同名显示器，最后是主显示器
+fn pick_monitor<'a>(state: &WindowState, screens: &'a [Screen], primary: Option<&'a Screen>) -> Option<&'a Screen> {
+    let title_bar = (state.x + 50, state.y + 20);
+
+    screens
+        .iter()
+        .find(|s| contains((s.position, s.size), title_bar))
+        .or_else(|| screens.iter().find(|s| state.monitor.is_some() && s.name == state.monitor))
+        .or(primary)
+        .or_else(|| screens.first())
+}
+
+fn contains((position, size): (PhysicalPosition<i32>, PhysicalSize<u32>), (x, y): (i32, i32)) -> bool {
+    x >= position.x
+        && y >= position.y
+        && x < position.x + size.width as i32
+        && y < position.y + size.height as i32
+}
+
+// 将窗口限制在显示器范围内，避免窗口出现在屏幕之外
+fn clamp_to_monitor(state: &mut WindowState, (position, size): (PhysicalPosition<i32>, PhysicalSize<u32>)) {
+    state.width = state.width.min(size.width);
+    state.height = state.height.min(size.height);
+    state.x = state
+        .x
+        .clamp(position.x, position.x + (size.width - state.width) as i32);
+    state.y = state
+        .y
+        .clamp(position.y, position.y + (size.height - state.height) as i32);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(x: i32, y: i32, width: u32, height: u32) -> WindowState {
+        WindowState {
+            x,
+            y,
+            width,
+            height,
+            maximized: false,
+            monitor: None,
+            visible: true,
+        }
+    }
+
+    fn screen(name: &str, x: i32, y: i32, width: u32, height: u32) -> Screen {
+        Screen {
+            name: Some(name.to_string()),
+            position: PhysicalPosition::new(x, y),
+            size: PhysicalSize::new(width, height),
+        }
+    }
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+        (PhysicalPosition::new(x, y), PhysicalSize::new(width, height))
+    }
+
+    #[test]
+    fn picks_the_monitor_under_the_title_bar() {
+        let screens = [screen("left", 0, 0, 1920, 1080), screen("right", 1920, 0, 2560, 1440)];
+
+        let picked = pick_monitor(&state(2000, 100, 800, 600), &screens, Some(&screens[0]));
+        assert_eq!(picked, Some(&screens[1]));
+    }
+
+    #[test]
+    fn falls_back_to_the_saved_monitor_then_the_primary() {
+        let screens = [screen("left", 0, 0, 1920, 1080), screen("right", 1920, 0, 2560, 1440)];
+        let mut off_screen = state(-5000, -5000, 800, 600);
+
+        assert_eq!(pick_monitor(&off_screen, &screens, Some(&screens[0])), Some(&screens[0]));
+        assert_eq!(pick_monitor(&off_screen, &screens, None), Some(&screens[0]));
+
+        off_screen.monitor = Some("right".to_string());
+        assert_eq!(pick_monitor(&off_screen, &screens, Some(&screens[0])), Some(&screens[1]));
+        assert_eq!(pick_monitor(&off_screen, &[], None), None);
+    }
+
+    #[test]
+    fn moves_off_screen_windows_back_onto_the_monitor() {
+        let mut window = state(-5000, 4000, 800, 600);
+        clamp_to_monitor(&mut window, rect(1920, 0, 2560, 1440));
+
+        assert_eq!((window.x, window.y, window.width, window.height), (1920, 840, 800, 600));
+    }
+
+    #[test]
+    fn shrinks_oversized_windows_to_the_monitor() {
+        let mut window = state(100, 100, 4000, 3000);
+        clamp_to_monitor(&mut window, rect(0, 0, 1920, 1080));
+
+        assert_eq!((window.x, window.y, window.width, window.height), (0, 0, 1920, 1080));
+    }
+
+    #[test]
+    fn keeps_windows_that_already_fit() {
+        let mut window = state(200, 150, 800, 600);
+        clamp_to_monitor(&mut window, rect(0, 0, 1920, 1080));
+
+        assert_eq!((window.x, window.y, window.width, window.height), (200, 150, 800, 600));
+    }
+}